@@ -1,8 +1,90 @@
 use anchor_lang::prelude::*;
 use anchor_lang::system_program;
+use anchor_lang::solana_program::instruction::{
+    get_stack_height, AccountMeta, Instruction, TRANSACTION_LEVEL_STACK_HEIGHT,
+};
+use anchor_lang::solana_program::program::invoke_signed;
+use solana_instructions_sysvar::{load_current_index_checked, load_instruction_at_checked};
+use solana_sdk_ids::{ed25519_program, sysvar::instructions as instructions_sysvar};
+use solana_sha256_hasher::hashv;
 
 declare_id!("27pnqQrAbmua19nkfGxcv8LLKmFwt9PZPfNTGmAJTinV");
 
+/// Denominator for all basis-point rates
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Number of tiers on the donation matching curve
+pub const MATCH_TIER_COUNT: usize = 3;
+
+/// Maximum number of team members sharing a vested payout
+pub const TEAM_SIZE: usize = 4;
+
+/// Codes reported by `InvariantViolation`
+pub const INVARIANT_VAULT_UNDERFUNDED: u8 = 1;
+pub const INVARIANT_FUNDS_ABOVE_TARGET: u8 = 2;
+pub const INVARIANT_FINALIZED_WITH_FUNDS: u8 = 3;
+pub const INVARIANT_RECEIPTS_EXCEED_FUNDS: u8 = 4;
+
+/// Buckets returned by `urgency_bucket`
+pub const URGENCY_EXPIRED: u8 = 0;
+pub const URGENCY_UNDER_DAY: u8 = 1;
+pub const URGENCY_UNDER_WEEK: u8 = 2;
+pub const URGENCY_UNDER_MONTH: u8 = 3;
+pub const URGENCY_LONGER: u8 = 4;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// `health_score` weights, in percent of the final score
+const HEALTH_WEIGHT_PROGRESS: u64 = 40;
+const HEALTH_WEIGHT_VELOCITY: u64 = 30;
+const HEALTH_WEIGHT_BACKERS: u64 = 20;
+const HEALTH_WEIGHT_TIME: u64 = 10;
+/// Backer count that earns the full backer component
+const HEALTH_FULL_BACKERS: u64 = 20;
+
+/// `creator_reputation` weights, in percent of the final score
+const REPUTATION_WEIGHT_SUCCESS: u64 = 70;
+const REPUTATION_WEIGHT_VOLUME: u64 = 30;
+/// Lamports raised across successful campaigns that earn the full volume component
+const REPUTATION_FULL_VOLUME: u64 = 100_000_000_000;
+
+/// SPL Token program, invoked by hand for the backer reward mint
+pub const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+/// Reward tokens use the same precision as SOL
+pub const REWARD_MINT_DECIMALS: u8 = 9;
+
+/// Size of an SPL Token mint account
+const MINT_ACCOUNT_LEN: u64 = 82;
+
+/// Most accounts `contributions_of` reads; keeps the result within return data limits
+pub const MAX_CONTRIBUTIONS_QUERY: usize = 100;
+
+/// Layout version prefixed to `export_state` blobs; bump when `Campaign` changes
+pub const STATE_EXPORT_VERSION: u8 = 1;
+
+/// Layout version leading `full_state` blobs; bump when `Campaign` changes.
+/// Clients must reject versions they do not know instead of guessing.
+pub const FULL_STATE_VERSION: u8 = 1;
+
+/// What `process_deadline` does once the deadline has passed
+pub const END_ACTION_AUTO_FINALIZE: u8 = 0;
+pub const END_ACTION_ENABLE_REFUNDS: u8 = 1;
+pub const END_ACTION_KEEP_OPEN: u8 = 2;
+/// Deposits pause until the authority extends the deadline
+pub const END_ACTION_PAUSE: u8 = 3;
+/// Deposits stop and only finalize remains; the campaign cannot be marked failed
+pub const END_ACTION_ALLOW_FINALIZE_ONLY: u8 = 4;
+
+/// Lifecycle of a campaign's creator bond
+pub const BOND_NONE: u8 = 0;
+pub const BOND_POSTED: u8 = 1;
+/// Taken back by the creator through `claim_bond`
+pub const BOND_RETURNED: u8 = 2;
+/// Being paid out to the donors by `slash_bond`
+pub const BOND_FORFEITED: u8 = 3;
+/// Every donor has been paid their share
+pub const BOND_DISTRIBUTED: u8 = 4;
 
 #[error_code]
 pub enum ErrorCode {
@@ -18,14 +100,203 @@ pub enum ErrorCode {
     MathOverflow,
     #[msg("Unauthorized")]
     Unauthorized,
+    #[msg("Match tiers must be contiguous")]
+    InvalidMatchTiers,
+    #[msg("Campaign accounting invariant violated")]
+    InvariantViolated,
+    #[msg("Receipt is not a contribution to this campaign")]
+    InvalidReceipt,
+    #[msg("Campaign is not finalized")]
+    CampaignNotFinalized,
+    #[msg("Instruction must be invoked via CPI")]
+    NotInvokedViaCpi,
+    #[msg("Campaign target not reached")]
+    TargetNotReached,
+    #[msg("Fee basis points must not exceed 10000")]
+    InvalidFee,
+    #[msg("Refunds are not enabled for this campaign")]
+    RefundsDisabled,
+    #[msg("Nothing to refund")]
+    NothingToRefund,
+    #[msg("Deposit does not match the authorization")]
+    AuthorizationMismatch,
+    #[msg("Campaign is cancelled")]
+    CampaignCancelled,
+    #[msg("Campaign account still exists")]
+    CampaignStillOpen,
+    #[msg("Nothing to reclaim")]
+    NothingToReclaim,
+    #[msg("Deposit above the large deposit threshold must be confirmed")]
+    DepositRequiresConfirmation,
+    #[msg("Large deposit must be confirmed in a later slot")]
+    ConfirmationTooEarly,
+    #[msg("Beneficiary is not in the registry")]
+    BeneficiaryNotAllowed,
+    #[msg("Deadline must be in the future")]
+    InvalidDeadline,
+    #[msg("Campaign deadline has passed")]
+    DeadlinePassed,
+    #[msg("Backup beneficiary requires a deadline and a non-negative delay")]
+    InvalidBackupBeneficiary,
+    #[msg("Campaign has no backup beneficiary")]
+    NoBackupBeneficiary,
+    #[msg("Backup beneficiary cannot finalize yet")]
+    BackupNotYetAllowed,
+    #[msg("Campaign funds do not exceed the target")]
+    NoOverflow,
+    #[msg("Overflow distribution is in progress")]
+    OverflowDistributionInProgress,
+    #[msg("Deposit must match one of the allowed tier amounts")]
+    InvalidTier,
+    #[msg("Tier amount exceeds what is left to the target")]
+    TierExceedsRemaining,
+    #[msg("Beneficiary cannot donate to this campaign")]
+    BeneficiaryCannotDonate,
+    #[msg("Team shares must name distinct members and sum to 10000 bps")]
+    InvalidTeamShares,
+    #[msg("Campaign has no team configured")]
+    NoTeam,
+    #[msg("Team campaigns must be finalized into vesting")]
+    TeamCampaignRequiresVesting,
+    #[msg("Vesting needs a positive duration and a cliff within it")]
+    InvalidVestingSchedule,
+    #[msg("Signer is not a member of this team")]
+    NotTeamMember,
+    #[msg("Nothing has vested yet")]
+    NothingToClaim,
+    #[msg("Salt does not match the anonymous deposit commitment")]
+    InvalidReveal,
+    #[msg("Campaign has failed")]
+    CampaignFailed,
+    #[msg("Unknown end action")]
+    InvalidEndAction,
+    #[msg("Partial withdrawals are disabled for this campaign")]
+    WithdrawalsDisabled,
+    #[msg("Funds have not reached the withdraw floor")]
+    WithdrawFloorNotMet,
+    #[msg("Withdrawal would dip into the required reserve")]
+    WithdrawReserveRequired,
+    #[msg("Withdraw floor and reserve cannot exceed the target")]
+    InvalidWithdrawConfig,
+    #[msg("State blob is malformed")]
+    InvalidStateBlob,
+    #[msg("State blob version is not supported")]
+    UnsupportedStateVersion,
+    #[msg("Campaign already has activity")]
+    CampaignNotFresh,
+    #[msg("Vault does not hold the imported funds")]
+    ImportUnderfunded,
+    #[msg("Minimum finalize amount cannot exceed the target")]
+    InvalidMinFinalizeAmount,
+    #[msg("Donor has reached the per-donor cap")]
+    DonorCapReached,
+    #[msg("Campaign does not issue backer rewards")]
+    NoRewards,
+    #[msg("Reward already claimed for this contribution")]
+    RewardAlreadyClaimed,
+    #[msg("Invalid overshoot tolerance")]
+    InvalidOvershootTolerance,
+    #[msg("Invalid rent split")]
+    InvalidRentSplit,
+    #[msg("Treasury does not match the protocol config")]
+    InvalidTreasury,
+    #[msg("round_up_to_tier needs at least one allowed amount")]
+    RoundUpWithoutTiers,
+    #[msg("Withdrawal would exceed the campaign's withdrawal cap")]
+    WithdrawCapReached,
+    #[msg("Invalid refund policy")]
+    InvalidRefundPolicy,
+    #[msg("Refund policy is immutable")]
+    RefundPolicyImmutable,
+    #[msg("Refunds are still locked for this contribution")]
+    RefundLocked,
+    #[msg("Refund window has closed")]
+    RefundWindowClosed,
+    #[msg("Refund would exceed the campaign's refund cap")]
+    RefundCapReached,
+    #[msg("Contribution is below the content unlock threshold")]
+    ContentLocked,
+    #[msg("Fee can no longer change once funds were raised")]
+    FeeLocked,
+    #[msg("Only donors can do this")]
+    NotADonor,
+    #[msg("Invalid streaming payout config")]
+    InvalidStreamConfig,
+    #[msg("Streaming campaigns only accept deposits through deposit")]
+    StreamingDepositOnly,
+    #[msg("Campaign has not received enough deposits to finalize")]
+    NotEnoughDeposits,
+    #[msg("Campaign has no arbiter")]
+    NoArbiter,
+    #[msg("A dispute is active")]
+    DisputeActive,
+    #[msg("No dispute is active")]
+    NoActiveDispute,
+    #[msg("Voucher signature does not match")]
+    InvalidVoucher,
+    #[msg("Voucher escrow cannot cover the deposit")]
+    InsufficientVoucherEscrow,
+    #[msg("Dust refund threshold exceeds the target")]
+    InvalidDustRefundThreshold,
+    #[msg("Creator has too many active campaigns")]
+    TooManyActiveCampaigns,
+    #[msg("Campaign is paused")]
+    CampaignPaused,
+    #[msg("Campaign is not paused")]
+    CampaignNotPaused,
+    #[msg("Deposit requires a valid social attestation")]
+    SocialRequired,
+    #[msg("Social gating requires an issuer")]
+    InvalidSocialConfig,
+    #[msg("No bond is held for this campaign")]
+    BondNotPosted,
+    #[msg("Bond can only be returned after a finalize or once no donors remain")]
+    BondNotReturnable,
+    #[msg("Bond can only be slashed on a failed campaign with donors")]
+    BondNotSlashable,
+    #[msg("Forfeited bond is still being paid out")]
+    BondDistributionInProgress,
+    #[msg("Too few slots since this donor's last deposit")]
+    DonationTooSoon,
+    #[msg("Too many accounts for one query")]
+    TooManyAccounts,
+    #[msg("Close the campaign's contribution accounts first")]
+    CampaignNotCleaned,
+    #[msg("Campaign key was archived and cannot be reused")]
+    CampaignArchived,
+    #[msg("Campaigns with backer rewards finalize through finalize_and_reward")]
+    RewardsRequireMint,
+    #[msg("Only an open campaign can be imported")]
+    ImportNotOpen,
+    #[msg("Imported deposits would have no Contribution accounts")]
+    ImportHasDeposits,
+    #[msg("Beneficiary account does not match the imported beneficiary")]
+    ImportBeneficiaryMismatch,
+    #[msg("Creator bond must be claimed or paid out first")]
+    BondOutstanding,
+    #[msg("Partial withdrawals already paid out donor funds")]
+    FundsWithdrawn,
+    #[msg("Large deposit confirmation is unavailable on streaming or social-gated campaigns")]
+    LargeDepositsUnconfirmable,
+    #[msg("This donor already raised a dispute on this campaign")]
+    DisputeAlreadyRaised,
 }
 
 #[program]
 pub mod test_project {
     use super::*;
 
-    pub fn initialize(ctx: Context<Initialize>, campaign_id: u64, target: u64) -> Result<()> {
-        require!(target>0, ErrorCode::InvalidAmount);
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        campaign_id: u64,
+        target: u64,
+        config: CampaignConfig,
+    ) -> Result<()> {
+        validate_campaign_config(target, &config, Clock::get()?.unix_timestamp)?;
+        require_beneficiary_allowed(
+            &ctx.accounts.protocol_config,
+            &ctx.accounts.beneficiary_registry,
+        )?;
 
         let campaign = &mut ctx.accounts.campaign;
         campaign.funds = 0;
@@ -34,34 +305,540 @@ pub mod test_project {
         campaign.authority = ctx.accounts.creator.key();
         campaign.beneficiary = ctx.accounts.beneficiary.key();
         campaign.is_finalized = false;
+        campaign.match_tiers = config.match_tiers;
+        campaign.match_pool = 0;
+        campaign.created_at = Clock::get()?.unix_timestamp;
+        campaign.strict_target = config.strict_target;
+        campaign.refund_policy = config.refund_policy;
+        campaign.large_deposit_threshold = config.large_deposit_threshold;
+        campaign.deadline = config.deadline;
+        campaign.backup_beneficiary = config.backup_beneficiary;
+        campaign.backup_delay = config.backup_delay;
+        campaign.allowed_amounts = config.allowed_amounts;
+        campaign.fee_bps = config.fee_bps;
+        campaign.block_beneficiary_deposits = config.block_beneficiary_deposits;
+        campaign.team = config.team;
+        campaign.end_action = config.end_action;
+        campaign.verbose_logs = config.verbose_logs;
+        campaign.allow_partial_withdrawals = config.allow_partial_withdrawals;
+        campaign.withdraw_floor = config.withdraw_floor;
+        campaign.withdraw_reserve = config.withdraw_reserve;
+        campaign.min_finalize_amount = config.min_finalize_amount;
+        campaign.max_per_donor = config.max_per_donor;
+        campaign.reward_rate = config.reward_rate;
+        campaign.overshoot_tolerance_bps = config.overshoot_tolerance_bps;
+        campaign.round_up_to_tier = config.round_up_to_tier;
+        campaign.max_total_withdrawals = config.max_total_withdrawals;
+        campaign.gated_content_hash = config.gated_content_hash;
+        campaign.unlock_threshold = config.unlock_threshold;
+        campaign.stream_payout = config.stream_payout;
+        campaign.stream_bps = config.stream_bps;
+        campaign.min_deposit_count = config.min_deposit_count;
+        campaign.arbiter = config.arbiter;
+        campaign.dust_refund_threshold = config.dust_refund_threshold;
+        campaign.require_social = config.require_social;
+        campaign.social_issuer = config.social_issuer;
+        campaign.bond_amount = config.bond_amount;
+        campaign.min_slot_gap = config.min_slot_gap;
+        campaign.cranker_reward = config.cranker_reward;
+
+        // The bond PDA also needs its rent, which goes back with the bond
+        if config.bond_amount > 0 {
+            let deposit = config
+                .bond_amount
+                .checked_add(Rent::get()?.minimum_balance(0))
+                .ok_or(ErrorCode::MathOverflow)?;
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.creator.to_account_info(),
+                        to: ctx.accounts.creator_bond.to_account_info(),
+                    },
+                ),
+                deposit,
+            )?;
+            campaign.bond_state = BOND_POSTED;
+        }
+
+        let profile = &mut ctx.accounts.creator_profile;
+        require_below_active_cap(&ctx.accounts.protocol_config, profile.active_campaigns)?;
+        profile.creator = ctx.accounts.creator.key();
+        profile.bump = ctx.bumps.creator_profile;
+        profile.campaigns_created = profile
+            .campaigns_created
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        profile.active_campaigns = profile
+            .active_campaigns
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        let stats = &mut ctx.accounts.global_stats;
+        stats.bump = ctx.bumps.global_stats;
+        stats.total_campaigns = stats
+            .total_campaigns
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        stats.active_campaigns = stats
+            .active_campaigns
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Dry run of the config checks `initialize` performs, so clients can
+    /// surface the exact error before paying for any accounts. The
+    /// beneficiary whitelist is not checked here.
+    pub fn validate_config(
+        _ctx: Context<ValidateConfig>,
+        target: u64,
+        config: CampaignConfig,
+    ) -> Result<()> {
+        validate_campaign_config(target, &config, Clock::get()?.unix_timestamp)
+    }
+
+    /// Authority calls off the campaign. Deposits stop and every donor can
+    /// take a full refund, whether or not refunds were enabled. Cancelling
+    /// with donors forfeits the creator bond to them.
+    pub fn cancel(ctx: Context<Cancel>) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.is_cancelled = true;
+        if campaign.bond_state == BOND_POSTED && campaign.donor_count > 0 {
+            forfeit_bond(campaign)?;
+        }
+        record_outcome(&mut ctx.accounts.creator_profile, false, 0, campaign.funds)?;
+
+        let stats = &mut ctx.accounts.global_stats;
+        stats.active_campaigns = stats
+            .active_campaigns
+            .checked_sub(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        stats.failed_campaigns = stats
+            .failed_campaigns
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Permissionless crank that applies the campaign's `end_action` once the
+    /// deadline has passed. Runs the action at most once; later calls, or calls
+    /// on a campaign that is already closed out, do nothing.
+    pub fn process_deadline(ctx: Context<ProcessDeadline>) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+        require!(
+            campaign.deadline > 0 && Clock::get()?.unix_timestamp >= campaign.deadline,
+            ErrorCode::CampaignStillOpen
+        );
+        if campaign.deadline_processed
+            || campaign.is_finalized
+            || campaign.is_cancelled
+            || campaign.is_failed
+        {
+            msg!("Deadline already processed");
+            return Ok(());
+        }
+
+        // Short of the target the campaign fails exactly as mark_failed would, so
+        // the outcome does not depend on which crank lands first. Once partial
+        // withdrawals were paid out it can no longer fail and settles instead.
+        let short = raised(campaign)? < campaign.target && campaign.total_withdrawn == 0;
+        match campaign.end_action {
+            END_ACTION_AUTO_FINALIZE if short => fail_campaign(
+                &mut ctx.accounts.campaign,
+                &mut ctx.accounts.creator_profile,
+                &mut ctx.accounts.global_stats,
+            )?,
+            END_ACTION_AUTO_FINALIZE => {
+                require!(
+                    campaign.team.iter().all(|share| share.share_bps == 0),
+                    ErrorCode::TeamCampaignRequiresVesting
+                );
+                let beneficiary = ctx.accounts.beneficiary.to_account_info();
+                settle(
+                    Settlement {
+                        campaign: &mut ctx.accounts.campaign,
+                        vault_lamports: &ctx.accounts.vault_lamports,
+                        vault_bump: ctx.bumps.vault_lamports,
+                        authority: ctx.accounts.authority.to_account_info(),
+                        global_stats: &mut ctx.accounts.global_stats,
+                        creator_profile: &mut ctx.accounts.creator_profile,
+                        protocol_config: ctx.accounts.protocol_config.to_account_info(),
+                        treasury: ctx.accounts.treasury.to_account_info(),
+                        system_program: ctx.accounts.system_program.to_account_info(),
+                        cranker: None,
+                        creates_reward_mint: false,
+                    },
+                    beneficiary,
+                )?;
+            }
+            END_ACTION_ENABLE_REFUNDS => {
+                ctx.accounts.campaign.refund_policy.refunds_enabled = true
+            }
+            END_ACTION_PAUSE => ctx.accounts.campaign.is_paused = true,
+            _ => {}
+        }
+
+        ctx.accounts.campaign.deadline_processed = true;
+        Ok(())
+    }
 
+    /// Authority resumes a campaign paused at its deadline by moving the
+    /// deadline out. `process_deadline` runs again at the new deadline.
+    pub fn extend_deadline(ctx: Context<ExtendDeadline>, new_deadline: i64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        require!(campaign.is_paused, ErrorCode::CampaignNotPaused);
+        require!(
+            new_deadline > Clock::get()?.unix_timestamp,
+            ErrorCode::InvalidDeadline
+        );
+        // The backup beneficiary's window moves with the deadline
+        campaign.deadline = new_deadline;
+        campaign.is_paused = false;
+        campaign.deadline_processed = false;
         Ok(())
     }
 
+    /// Permissionless: flags a campaign that reached its deadline short of the
+    /// target as failed, which opens fee-free refunds and the match claw-back
+    pub fn mark_failed(ctx: Context<MarkFailed>) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        require!(
+            campaign.deadline > 0 && Clock::get()?.unix_timestamp >= campaign.deadline,
+            ErrorCode::CampaignStillOpen
+        );
+        // Paused campaigns may still resume; finalize-only campaigns never fail
+        require!(
+            !matches!(
+                campaign.end_action,
+                END_ACTION_KEEP_OPEN | END_ACTION_PAUSE | END_ACTION_ALLOW_FINALIZE_ONLY
+            ),
+            ErrorCode::CampaignStillOpen
+        );
+        require!(raised(campaign)? < campaign.target, ErrorCode::TargetAlreadyReached);
+        fail_campaign(
+            campaign,
+            &mut ctx.accounts.creator_profile,
+            &mut ctx.accounts.global_stats,
+        )
+    }
+
+    /// Platform-wide campaign counts by state
+    pub fn get_global_stats(ctx: Context<ReadGlobalStats>) -> Result<GlobalStatsView> {
+        let stats = &ctx.accounts.global_stats;
+        Ok(GlobalStatsView {
+            total_campaigns: stats.total_campaigns,
+            active_campaigns: stats.active_campaigns,
+            finalized_campaigns: stats.finalized_campaigns,
+            failed_campaigns: stats.failed_campaigns,
+        })
+    }
 
-    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+    /// Authority tops up the pool used to match donations along the tier curve
+    pub fn fund_match(ctx: Context<FundMatch>, amount: u64) -> Result<()> {
         require!(amount > 0, ErrorCode::InvalidAmount);
 
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: ctx.accounts.vault_lamports.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.match_pool = campaign
+            .match_pool
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        Ok(())
+    }
+
+
+    /// Matcher takes back everything it put in once the campaign has failed or
+    /// been cancelled: the match already credited to donors plus the unused pool.
+    /// Donor refunds afterwards return only the donors' own contributions.
+    pub fn reclaim_match(ctx: Context<ReclaimMatch>) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        require!(
+            campaign.is_failed || campaign.is_cancelled,
+            ErrorCode::CampaignStillOpen
+        );
+        require!(!campaign.match_reclaimed, ErrorCode::NothingToReclaim);
+
+        let amount = campaign
+            .total_matched
+            .checked_add(campaign.match_pool)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(amount > 0, ErrorCode::NothingToReclaim);
+
+        campaign.funds = campaign
+            .funds
+            .checked_sub(campaign.total_matched)
+            .ok_or(ErrorCode::MathOverflow)?;
+        campaign.total_matched = 0;
+        campaign.match_pool = 0;
+        campaign.match_reclaimed = true;
+
+        let campaign_key = campaign.key();
+        transfer_from_vault(
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.vault_lamports.to_account_info(),
+            &campaign_key,
+            ctx.bumps.vault_lamports,
+            &ctx.accounts.authority.to_account_info(),
+            amount,
+        )
+    }
+
+    /// Donor freezes payouts until the campaign's arbiter rules on the dispute
+    pub fn raise_dispute(ctx: Context<RaiseDispute>) -> Result<()> {
         let campaign = &mut ctx.accounts.campaign;
-        require!(!campaign.is_finalized, ErrorCode::CampaignFinalized);
+        require!(campaign.arbiter != Pubkey::default(), ErrorCode::NoArbiter);
+        require!(!campaign.dispute_active, ErrorCode::DisputeActive);
+        // One dispute per donor, so a resolved dispute cannot be raised again
+        // right away to keep the payout frozen
+        let contribution = &mut ctx.accounts.contribution;
+        require!(!contribution.dispute_raised, ErrorCode::DisputeAlreadyRaised);
+        contribution.dispute_raised = true;
 
-        // Remaining amount to reach target
-        let remaining = campaign
-            .target
-            .checked_sub(campaign.funds)
+        campaign.dispute_active = true;
+        campaign.dispute_count = campaign
+            .dispute_count
+            .checked_add(1)
             .ok_or(ErrorCode::MathOverflow)?;
 
-        require!(remaining > 0, ErrorCode::TargetAlreadyReached);
+        emit!(DisputeRaised {
+            campaign: campaign.key(),
+            donor: ctx.accounts.donor.key(),
+            dispute_count: campaign.dispute_count,
+        });
+        Ok(())
+    }
+
+    /// Arbiter closes the active dispute. In favor of the donors the campaign
+    /// fails, opening fee-free refunds; otherwise payouts are unfrozen.
+    pub fn resolve_dispute(ctx: Context<ResolveDispute>, favor_donors: bool) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        require!(campaign.dispute_active, ErrorCode::NoActiveDispute);
+        campaign.dispute_active = false;
+
+        if favor_donors {
+            require!(campaign.total_withdrawn == 0, ErrorCode::FundsWithdrawn);
+            fail_campaign(
+                campaign,
+                &mut ctx.accounts.creator_profile,
+                &mut ctx.accounts.global_stats,
+            )?;
+        }
+
+        emit!(DisputeResolved {
+            campaign: campaign.key(),
+            arbiter: ctx.accounts.arbiter.key(),
+            favor_donors,
+        });
+        Ok(())
+    }
+
+    /// Creator takes the bond and its rent back after any finalize, or after a
+    /// cancel or failure once no donors remain to compensate
+    pub fn claim_bond(ctx: Context<ClaimBond>) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        require!(campaign.bond_state == BOND_POSTED, ErrorCode::BondNotPosted);
+        require!(
+            campaign.is_finalized
+                || ((campaign.is_cancelled || campaign.is_failed) && campaign.donor_count == 0),
+            ErrorCode::BondNotReturnable
+        );
+        campaign.bond_state = BOND_RETURNED;
 
-        // "counted" amount: we only accept up to remaining
-        let counted = amount.min(remaining);
+        let bond = ctx.accounts.creator_bond.to_account_info();
+        let lamports = bond.lamports();
+        transfer_from_bond(
+            &ctx.accounts.system_program.to_account_info(),
+            &bond,
+            &campaign.key(),
+            ctx.bumps.creator_bond,
+            &ctx.accounts.authority.to_account_info(),
+            lamports,
+        )
+    }
+
+    /// Arbiter or protocol admin forfeits the bond of a failed campaign and
+    /// pays it to the donors pro-rata, paging through `count` (contribution,
+    /// donor) pairs in `remaining_accounts`. A bond already forfeited by
+    /// `cancel` can be paged out by anyone. Refunds wait until every donor is
+    /// paid; the last donor absorbs the rounding dust and the bond's rent.
+    pub fn slash_bond<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SlashBond<'info>>,
+        count: u16,
+    ) -> Result<()> {
+        let pairs = ctx.remaining_accounts;
+        require!(
+            count > 0 && pairs.len() == usize::from(count) * 2,
+            ErrorCode::InvalidReceipt
+        );
 
-        if counted < amount {
-            msg!(
-                "Deposit amount reduced from {} to {} to avoid exceeding target",
-                amount,
-                counted
+        let campaign = &mut ctx.accounts.campaign;
+        if campaign.bond_state == BOND_POSTED {
+            let slasher = ctx.accounts.slasher.key();
+            let admin = load_optional::<ProtocolConfig>(&ctx.accounts.protocol_config)?
+                .map(|config| config.admin);
+            require!(
+                (campaign.arbiter != Pubkey::default() && slasher == campaign.arbiter)
+                    || admin == Some(slasher),
+                ErrorCode::Unauthorized
             );
+            require!(
+                campaign.is_failed && campaign.donor_count > 0,
+                ErrorCode::BondNotSlashable
+            );
+            forfeit_bond(campaign)?;
+        }
+        require!(campaign.bond_state == BOND_FORFEITED, ErrorCode::BondNotPosted);
+
+        let campaign_key = campaign.key();
+        let bond = ctx.accounts.creator_bond.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let rent_reserve = Rent::get()?.minimum_balance(0);
+
+        for pair in pairs.chunks(2) {
+            let (contribution_info, donor) = (&pair[0], &pair[1]);
+            require!(contribution_info.is_writable, ErrorCode::InvalidReceipt);
+            let mut contribution =
+                load_optional::<Contribution>(contribution_info)?.ok_or(ErrorCode::InvalidReceipt)?;
+            require_keys_eq!(contribution.campaign, campaign_key, ErrorCode::InvalidReceipt);
+            require_keys_eq!(contribution.donor, donor.key(), ErrorCode::InvalidReceipt);
+
+            if contribution.bond_paid || contribution.amount == 0 {
+                continue;
+            }
+
+            campaign.bond_processed = campaign
+                .bond_processed
+                .checked_add(1)
+                .ok_or(ErrorCode::MathOverflow)?;
+            // Partial payouts keep the bond account rent-exempt
+            let share = if campaign.bond_processed == campaign.donor_count {
+                bond.lamports()
+            } else {
+                let share = u128::from(contribution.amount) * u128::from(campaign.bond_amount)
+                    / u128::from(campaign.bond_basis);
+                u64::try_from(share)
+                    .map_err(|_| ErrorCode::MathOverflow)?
+                    .min(bond.lamports().saturating_sub(rent_reserve))
+            };
+
+            campaign.bond_distributed = campaign
+                .bond_distributed
+                .checked_add(share)
+                .ok_or(ErrorCode::MathOverflow)?;
+            contribution.bond_paid = true;
+            contribution.try_serialize(&mut &mut contribution_info.try_borrow_mut_data()?[..])?;
+
+            if share > 0 {
+                transfer_from_bond(
+                    &system_program,
+                    &bond,
+                    &campaign_key,
+                    ctx.bumps.creator_bond,
+                    donor,
+                    share,
+                )?;
+            }
+
+            emit!(BondShareDistributed {
+                campaign: campaign_key,
+                donor: donor.key(),
+                amount: share,
+            });
+        }
+
+        if campaign.bond_processed >= campaign.donor_count {
+            campaign.bond_state = BOND_DISTRIBUTED;
+        }
+
+        Ok(())
+    }
+
+    /// Issuer attests that `subject` has a linked social account, until
+    /// `expires_at` (0 for no expiry). Re-issuing refreshes the expiry.
+    pub fn issue_social_attestation(
+        ctx: Context<IssueSocialAttestation>,
+        subject: Pubkey,
+        expires_at: i64,
+    ) -> Result<()> {
+        let attestation = &mut ctx.accounts.attestation;
+        attestation.issuer = ctx.accounts.issuer.key();
+        attestation.subject = subject;
+        attestation.expires_at = expires_at;
+        attestation.bump = ctx.bumps.attestation;
+        Ok(())
+    }
+
+    /// Issuer withdraws an attestation and takes back its rent
+    pub fn revoke_social_attestation(_ctx: Context<RevokeSocialAttestation>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Returns the donor's receipt number
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<u64> {
+        let amount = round_up_to_tier(
+            &ctx.accounts.campaign,
+            &ctx.accounts.contribution,
+            &ctx.accounts.donor.to_account_info(),
+            amount,
+        )?;
+        require_below_large_deposit_threshold(&ctx.accounts.campaign, amount)?;
+        require_social_attestation(
+            &ctx.accounts.campaign,
+            &ctx.accounts.social_attestation,
+            &ctx.accounts.donor.key(),
+        )?;
+
+        let counted = record_deposit(
+            &mut ctx.accounts.campaign,
+            &mut ctx.accounts.contribution,
+            ctx.accounts.donor.key(),
+            ctx.bumps.contribution,
+            amount,
+            Some((&mut ctx.accounts.donor_profile, ctx.bumps.donor_profile)),
+        )?;
+
+        // Streaming campaigns send stream_bps of each deposit straight to the
+        // beneficiary; only the rest is held in the vault
+        let campaign = &mut ctx.accounts.campaign;
+        let streamed = if campaign.stream_payout {
+            bps_of(counted, campaign.stream_bps)?
+        } else {
+            0
+        };
+        if streamed > 0 {
+            campaign.funds = campaign
+                .funds
+                .checked_sub(streamed)
+                .ok_or(ErrorCode::MathOverflow)?;
+            campaign.total_streamed = campaign
+                .total_streamed
+                .checked_add(streamed)
+                .ok_or(ErrorCode::MathOverflow)?;
+
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.donor.to_account_info(),
+                        to: ctx.accounts.beneficiary.to_account_info(),
+                    },
+                ),
+                streamed,
+            )?;
         }
 
         // Transfer counted lamports from donor -> vault_lamports (CPI to System Program)
@@ -73,149 +850,4187 @@ pub mod test_project {
                     to: ctx.accounts.vault_lamports.to_account_info(),
                 },
             ),
-            counted,
+            counted - streamed,
         )?;
 
-        // Update accounted funds
+        Ok(ctx.accounts.contribution.receipt_number)
+    }
+
+    /// Donor backs out and takes their contribution back. The refund fee is
+    /// kept in the campaign for the beneficiary; any match credited on the
+    /// donor's deposits goes back to the match pool.
+    pub fn refund(ctx: Context<Refund>) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        // Cancelled and failed campaigns always refund in full, outside the policy
+        let closed = campaign.is_cancelled || campaign.is_failed;
+        let policy = campaign.refund_policy;
+        require!(policy.refunds_enabled || closed, ErrorCode::RefundsDisabled);
+        // Refunds are owed in full, which partial withdrawals no longer leave room for
+        require!(campaign.total_withdrawn == 0, ErrorCode::FundsWithdrawn);
+        require!(
+            !campaign.overflow_in_progress,
+            ErrorCode::OverflowDistributionInProgress
+        );
+        require!(
+            campaign.bond_state != BOND_FORFEITED,
+            ErrorCode::BondDistributionInProgress
+        );
+
+        let contribution = &mut ctx.accounts.contribution;
+        let gross = contribution.amount;
+        require!(gross > 0, ErrorCode::NothingToRefund);
+
+        let total_refunded = campaign
+            .total_refunded
+            .checked_add(gross)
+            .ok_or(ErrorCode::MathOverflow)?;
+        if !closed {
+            let now = Clock::get()?.unix_timestamp;
+            let since = now.saturating_sub(contribution.first_contributed_at);
+            require!(since >= policy.lockup_seconds, ErrorCode::RefundLocked);
+            require!(
+                policy.refund_window == 0 || since < policy.refund_window,
+                ErrorCode::RefundWindowClosed
+            );
+            require!(
+                policy.max_total_refunds == 0 || total_refunded <= policy.max_total_refunds,
+                ErrorCode::RefundCapReached
+            );
+        }
+        campaign.total_refunded = total_refunded;
+
+        let profile = &mut ctx.accounts.donor_profile;
+        profile.donor = ctx.accounts.donor.key();
+        profile.bump = ctx.bumps.donor_profile;
+        profile.total_refunded = profile
+            .total_refunded
+            .checked_add(gross)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        // The streamed share already reached the beneficiary and cannot come back
+        let streamed = if campaign.stream_payout {
+            bps_of(gross, campaign.stream_bps)?
+        } else {
+            0
+        };
+        // A cancelled or failed campaign has no beneficiary payout to compensate
+        let fee = if closed {
+            0
+        } else {
+            bps_of(gross, policy.refund_penalty_bps)?.min(gross - streamed)
+        };
+        let net = gross - streamed - fee;
+
+        // Once the matcher has reclaimed, the donor's match is no longer in funds
+        let matched = if campaign.match_reclaimed {
+            0
+        } else {
+            contribution.matched
+        };
+
         campaign.funds = campaign
             .funds
-            .checked_add(counted)
+            .checked_sub(net)
+            .and_then(|funds| funds.checked_sub(matched))
+            .ok_or(ErrorCode::MathOverflow)?;
+        campaign.match_pool = campaign
+            .match_pool
+            .checked_add(matched)
+            .ok_or(ErrorCode::MathOverflow)?;
+        campaign.total_matched = campaign
+            .total_matched
+            .checked_sub(matched)
+            .ok_or(ErrorCode::MathOverflow)?;
+        campaign.donor_count = campaign
+            .donor_count
+            .checked_sub(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        campaign.contributed = campaign
+            .contributed
+            .checked_sub(gross)
             .ok_or(ErrorCode::MathOverflow)?;
 
-        Ok(())
-    }
-
-    pub fn finalize(ctx: Context<Finalize>) -> Result<()> {
-        
-        // Ensure caller is the beneficiary (Anchor also checks via has_one + Signer)
-        require_keys_eq!(
-            ctx.accounts.beneficiary.key(),
-            ctx.accounts.campaign.beneficiary,
-            ErrorCode::Unauthorized
-        );
-        
-        // Transfer exactly accounted funds from vault -> beneficiary
-        let amount = ctx.accounts.campaign.funds;
-        require!(amount > 0, ErrorCode::NothingToFinalize);
+        contribution.amount = 0;
+        contribution.matched = 0;
 
-        // PDA signer seeds for lamports vault
-        let campaign_key = ctx.accounts.campaign.key();
+        let campaign_key = campaign.key();
         let vault_lamports_seeds: &[&[u8]] = &[
             b"vault_lamports",
             campaign_key.as_ref(),
             &[ctx.bumps.vault_lamports],
         ];
 
-        // CPI transfer signed by PDA
         system_program::transfer(
             CpiContext::new_with_signer(
                 ctx.accounts.system_program.to_account_info(),
                 system_program::Transfer {
                     from: ctx.accounts.vault_lamports.to_account_info(),
-                    to: ctx.accounts.beneficiary.to_account_info(),
+                    to: ctx.accounts.donor.to_account_info(),
                 },
                 &[vault_lamports_seeds],
             ),
-            amount,
+            net,
         )?;
 
-        let remaining = ctx.accounts.vault_lamports.to_account_info().lamports();
-        if remaining > 0 {
-            system_program::transfer(
-                CpiContext::new_with_signer(
-                    ctx.accounts.system_program.to_account_info(),
-                    system_program::Transfer {
-                        from: ctx.accounts.vault_lamports.to_account_info(),
-                        to: ctx.accounts.authority.to_account_info(),
-                    },
-                    &[vault_lamports_seeds],
-                ),
-                remaining,
-            )?;
-        }
-
-        // Mark campaign finalized and zero out accounted funds (optional but nice)
-        let campaign = &mut ctx.accounts.campaign;
-        campaign.is_finalized = true;
-        campaign.funds = 0;
-
-        // Vault will be closed automatically by Anchor because of `close = authority`
-        // Any remaining lamports on vault_lamports go to authority.
+        emit!(Refunded {
+            campaign: campaign_key,
+            donor: ctx.accounts.donor.key(),
+            gross,
+            fee,
+            net,
+        });
 
         Ok(())
     }
 
-}
+    /// Returns any funds above the target to donors pro-rata, paging through
+    /// `count` (contribution, donor) pairs passed in `remaining_accounts`.
+    /// The first call snapshots the overflow; the last donor absorbs rounding dust.
+    pub fn distribute_overflow<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DistributeOverflow<'info>>,
+        count: u16,
+    ) -> Result<()> {
+        let pairs = ctx.remaining_accounts;
+        require!(
+            count > 0 && pairs.len() == usize::from(count) * 2,
+            ErrorCode::InvalidReceipt
+        );
 
-#[account]
-#[derive(InitSpace)]
-pub struct Campaign{
-   pub funds: u64,
-   pub target: u64,
-   pub campaign_id: u64,
-   pub authority: Pubkey,
-   pub beneficiary: Pubkey,
-   pub is_finalized: bool
-}
+        let campaign = &mut ctx.accounts.campaign;
+        if !campaign.overflow_in_progress {
+            // The tolerated overshoot belongs to the campaign, only funds above it overflow
+            let ceiling = deposit_ceiling(campaign)?;
+            require!(campaign.funds > ceiling, ErrorCode::NoOverflow);
+            campaign.overflow_in_progress = true;
+            campaign.overflow_pending = false;
+            campaign.overflow_round = campaign
+                .overflow_round
+                .checked_add(1)
+                .ok_or(ErrorCode::MathOverflow)?;
+            campaign.overflow_total = campaign.funds - ceiling;
+            // Match, fee and unrevealed anonymous lamports are in funds but belong
+            // to no Contribution, so only the donors' own amounts weigh the shares
+            campaign.overflow_basis = campaign.contributed.max(1);
+            campaign.overflow_distributed = 0;
+            campaign.overflow_processed = 0;
+        }
 
-#[derive(Accounts)]
-#[instruction(campaign_id: u64)]
-pub struct Initialize<'info> {
-    #[account(mut)]
-    pub creator: Signer<'info>,
+        let campaign_key = campaign.key();
+        let vault = ctx.accounts.vault_lamports.to_account_info();
+        let beneficiary = ctx.accounts.beneficiary.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
 
-    /// CHECK: we only read the pubkey and store it
-    pub beneficiary: UncheckedAccount<'info>,
+        for pair in pairs.chunks(2) {
+            let (contribution_info, donor) = (&pair[0], &pair[1]);
+            require!(contribution_info.is_writable, ErrorCode::InvalidReceipt);
+            let mut contribution =
+                load_optional::<Contribution>(contribution_info)?.ok_or(ErrorCode::InvalidReceipt)?;
+            require_keys_eq!(contribution.campaign, campaign_key, ErrorCode::InvalidReceipt);
+            require_keys_eq!(contribution.donor, donor.key(), ErrorCode::InvalidReceipt);
 
-    #[account(
-        init,
-        payer = creator,
-        space = 8 + Campaign::INIT_SPACE,
-        seeds = [b"campaign", creator.key().as_ref(), &campaign_id.to_le_bytes()],
-        bump
-    )]
-    pub campaign: Account<'info, Campaign>,
+            // Each donor is paid at most once per distribution
+            if contribution.overflow_round == campaign.overflow_round || contribution.amount == 0 {
+                continue;
+            }
 
-    #[account(
-        init,
-        payer = creator,
-        space = 0,
-        owner = system_program::ID,
-        seeds = [b"vault_lamports", campaign.key().as_ref()],
-        bump
-    )]
+            campaign.overflow_processed = campaign
+                .overflow_processed
+                .checked_add(1)
+                .ok_or(ErrorCode::MathOverflow)?;
+            let share = if campaign.overflow_processed == campaign.donor_count {
+                campaign.overflow_total - campaign.overflow_distributed
+            } else {
+                let share = u128::from(contribution.amount) * u128::from(campaign.overflow_total)
+                    / u128::from(campaign.overflow_basis);
+                u64::try_from(share).map_err(|_| ErrorCode::MathOverflow)?
+            };
 
-    /// CHECK: system-owned PDA used only for lamport transfers
-    pub vault_lamports: UncheckedAccount<'info>,
+            campaign.overflow_distributed = campaign
+                .overflow_distributed
+                .checked_add(share)
+                .ok_or(ErrorCode::MathOverflow)?;
+            campaign.funds = campaign
+                .funds
+                .checked_sub(share)
+                .ok_or(ErrorCode::MathOverflow)?;
+            let returned = share.min(contribution.amount);
+            contribution.amount -= returned;
+            campaign.contributed = campaign.contributed.saturating_sub(returned);
+            contribution.overflow_round = campaign.overflow_round;
+            contribution.try_serialize(&mut &mut contribution_info.try_borrow_mut_data()?[..])?;
 
-    pub system_program: Program<'info, System>,
-}
+            // A share below the dust threshold is not worth a transfer to the
+            // donor, so it goes to the beneficiary instead
+            if share > 0 && share < campaign.dust_refund_threshold {
+                campaign.overflow_dust_redirected = campaign
+                    .overflow_dust_redirected
+                    .checked_add(share)
+                    .ok_or(ErrorCode::MathOverflow)?;
+                transfer_from_vault(
+                    &system_program,
+                    &vault,
+                    &campaign_key,
+                    ctx.bumps.vault_lamports,
+                    &beneficiary,
+                    share,
+                )?;
+                emit!(OverflowDustRedirected {
+                    campaign: campaign_key,
+                    donor: donor.key(),
+                    amount: share,
+                });
+                continue;
+            }
 
-#[derive(Accounts)]
-pub struct Deposit<'info> {
-    #[account(mut)]
-    pub donor: Signer<'info>,
+            if share > 0 {
+                transfer_from_vault(
+                    &system_program,
+                    &vault,
+                    &campaign_key,
+                    ctx.bumps.vault_lamports,
+                    donor,
+                    share,
+                )?;
+            }
 
-    #[account(
-        mut,
-        seeds = [b"campaign", campaign.authority.as_ref(), &campaign.campaign_id.to_le_bytes()],
-        bump
-    )]
-    pub campaign: Account<'info, Campaign>,
+            emit!(OverflowDistributed {
+                campaign: campaign_key,
+                donor: donor.key(),
+                amount: share,
+            });
+        }
 
-    #[account(
-        mut,
-        seeds = [b"vault_lamports", campaign.key().as_ref()],
-        bump
-    )]
-    /// CHECK: system-owned PDA used only for lamport transfers
-    pub vault_lamports: UncheckedAccount<'info>,
+        if campaign.overflow_processed >= campaign.donor_count {
+            campaign.overflow_in_progress = false;
+        }
 
-    pub system_program: Program<'info, System>
+        Ok(())
+    }
 
-}
+    /// Deposit on behalf of another program's PDA. The PDA signs through
+    /// `invoke_signed` and keys the contribution; `payer` supplies the lamports
+    /// and may be the same account as `donor`.
+    pub fn deposit_cpi(ctx: Context<DepositCpi>, amount: u64) -> Result<()> {
+        require_side_deposit_allowed(&ctx.accounts.campaign)?;
+        require!(
+            get_stack_height() > TRANSACTION_LEVEL_STACK_HEIGHT,
+            ErrorCode::NotInvokedViaCpi
+        );
+        require_below_large_deposit_threshold(&ctx.accounts.campaign, amount)?;
 
-#[derive(Accounts)]
+        let counted = record_deposit(
+            &mut ctx.accounts.campaign,
+            &mut ctx.accounts.contribution,
+            ctx.accounts.donor.key(),
+            ctx.bumps.contribution,
+            amount,
+            Some((&mut ctx.accounts.donor_profile, ctx.bumps.donor_profile)),
+        )?;
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: ctx.accounts.vault_lamports.to_account_info(),
+                },
+            ),
+            counted,
+        )?;
+
+        Ok(())
+    }
+
+    /// Donor escrows `amount` lamports that `relayer` may later deposit for them
+    pub fn authorize_deposit(
+        ctx: Context<AuthorizeDeposit>,
+        amount: u64,
+        relayer: Pubkey,
+    ) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.donor.to_account_info(),
+                    to: ctx.accounts.authorization.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let authorization = &mut ctx.accounts.authorization;
+        authorization.campaign = ctx.accounts.campaign.key();
+        authorization.donor = ctx.accounts.donor.key();
+        authorization.relayer = relayer;
+        authorization.amount = amount;
+        authorization.bump = ctx.bumps.authorization;
+
+        Ok(())
+    }
+
+    /// Relayer submits a pre-authorized deposit. The escrowed lamports move into
+    /// the vault and whatever the target clamp left over goes back to the donor
+    /// when the authorization is closed.
+    pub fn deposit_delegated(ctx: Context<DepositDelegated>, amount: u64) -> Result<()> {
+        require_side_deposit_allowed(&ctx.accounts.campaign)?;
+        require_below_large_deposit_threshold(&ctx.accounts.campaign, amount)?;
+        require!(
+            amount == ctx.accounts.authorization.amount,
+            ErrorCode::AuthorizationMismatch
+        );
+
+        let counted = record_deposit(
+            &mut ctx.accounts.campaign,
+            &mut ctx.accounts.contribution,
+            ctx.accounts.donor.key(),
+            ctx.bumps.contribution,
+            amount,
+            Some((&mut ctx.accounts.donor_profile, ctx.bumps.donor_profile)),
+        )?;
+
+        // The authorization is owned by this program, so lamports move directly
+        ctx.accounts.authorization.sub_lamports(counted)?;
+        ctx.accounts.vault_lamports.add_lamports(counted)?;
+
+        Ok(())
+    }
+
+    /// Donor cancels an unused authorization and gets the escrow back
+    pub fn revoke_deposit_authorization(_ctx: Context<RevokeDepositAuthorization>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Donor tops up the escrow that signed vouchers draw from
+    pub fn fund_voucher_escrow(ctx: Context<FundVoucherEscrow>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.donor.to_account_info(),
+                    to: ctx.accounts.escrow.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.donor = ctx.accounts.donor.key();
+        escrow.bump = ctx.bumps.escrow;
+
+        Ok(())
+    }
+
+    /// Donor closes the voucher escrow and takes back whatever is left
+    pub fn close_voucher_escrow(_ctx: Context<CloseVoucherEscrow>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Gasless deposit: a relayer submits a voucher the donor signed off-chain.
+    /// The transaction must carry an ed25519 program instruction, right before
+    /// this one, verifying the donor's signature over
+    /// `campaign || donor || amount (LE) || nonce (LE)`. Each nonce can be used
+    /// once per donor and campaign. The counted amount moves from the donor's
+    /// voucher escrow into the vault; a clamped remainder stays in the escrow.
+    pub fn deposit_voucher(ctx: Context<DepositVoucher>, amount: u64, nonce: u64) -> Result<()> {
+        require_side_deposit_allowed(&ctx.accounts.campaign)?;
+        require_below_large_deposit_threshold(&ctx.accounts.campaign, amount)?;
+
+        let campaign_key = ctx.accounts.campaign.key();
+        let donor_key = ctx.accounts.donor.key();
+        let message = [
+            campaign_key.as_ref(),
+            donor_key.as_ref(),
+            &amount.to_le_bytes(),
+            &nonce.to_le_bytes(),
+        ]
+        .concat();
+        verify_ed25519_signature(&ctx.accounts.instructions, &donor_key, &message)?;
+
+        let voucher_nonce = &mut ctx.accounts.voucher_nonce;
+        voucher_nonce.campaign = campaign_key;
+        voucher_nonce.donor = donor_key;
+        voucher_nonce.nonce = nonce;
+        voucher_nonce.bump = ctx.bumps.voucher_nonce;
+
+        // The escrow keeps its own rent; only the balance above it is spendable
+        let escrow = ctx.accounts.escrow.to_account_info();
+        let spendable = escrow
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(escrow.data_len()));
+        require!(spendable >= amount, ErrorCode::InsufficientVoucherEscrow);
+
+        let counted = record_deposit(
+            &mut ctx.accounts.campaign,
+            &mut ctx.accounts.contribution,
+            donor_key,
+            ctx.bumps.contribution,
+            amount,
+            Some((&mut ctx.accounts.donor_profile, ctx.bumps.donor_profile)),
+        )?;
+
+        // The escrow is owned by this program, so lamports move directly
+        ctx.accounts.escrow.sub_lamports(counted)?;
+        ctx.accounts.vault_lamports.add_lamports(counted)?;
+
+        Ok(())
+    }
+
+    /// First step of a large deposit: the lamports are escrowed and only count
+    /// toward the campaign once the donor confirms in a later transaction
+    pub fn request_large_deposit(ctx: Context<RequestLargeDeposit>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.donor.to_account_info(),
+                    to: ctx.accounts.pending_deposit.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let pending = &mut ctx.accounts.pending_deposit;
+        pending.campaign = ctx.accounts.campaign.key();
+        pending.donor = ctx.accounts.donor.key();
+        pending.amount = amount;
+        pending.requested_slot = Clock::get()?.slot;
+        pending.bump = ctx.bumps.pending_deposit;
+
+        Ok(())
+    }
+
+    /// Counts an escrowed large deposit. Any part clamped by the target goes
+    /// back to the donor with the escrow rent.
+    pub fn confirm_large_deposit(ctx: Context<ConfirmLargeDeposit>) -> Result<()> {
+        require_side_deposit_allowed(&ctx.accounts.campaign)?;
+        require!(
+            Clock::get()?.slot > ctx.accounts.pending_deposit.requested_slot,
+            ErrorCode::ConfirmationTooEarly
+        );
+
+        let counted = record_deposit(
+            &mut ctx.accounts.campaign,
+            &mut ctx.accounts.contribution,
+            ctx.accounts.donor.key(),
+            ctx.bumps.contribution,
+            ctx.accounts.pending_deposit.amount,
+            Some((&mut ctx.accounts.donor_profile, ctx.bumps.donor_profile)),
+        )?;
+
+        ctx.accounts.pending_deposit.sub_lamports(counted)?;
+        ctx.accounts.vault_lamports.add_lamports(counted)?;
+
+        Ok(())
+    }
+
+    /// Donor withdraws an unconfirmed large deposit in full
+    pub fn cancel_large_deposit(_ctx: Context<CancelLargeDeposit>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Deposit without a named contribution. The funds are tracked under
+    /// `commitment` = sha256(donor || salt) so the donor can later claim them
+    /// with `reveal_contribution`. Repeat deposits under the same commitment add up.
+    pub fn deposit_anonymous(
+        ctx: Context<DepositAnonymous>,
+        amount: u64,
+        commitment: [u8; 32],
+    ) -> Result<()> {
+        require_side_deposit_allowed(&ctx.accounts.campaign)?;
+        require_below_large_deposit_threshold(&ctx.accounts.campaign, amount)?;
+
+        // Book against a scratch contribution so matching follows this commitment's
+        // total. It carries a donor so record_deposit does not count it as a new account.
+        let anonymous = &mut ctx.accounts.anonymous_deposit;
+        let mut scratch = Contribution {
+            campaign: ctx.accounts.campaign.key(),
+            donor: ctx.accounts.payer.key(),
+            amount: anonymous.amount,
+            matched: anonymous.matched,
+            bump: 0,
+            first_contributed_at: anonymous.first_deposited_at,
+            overflow_round: 0,
+            reward_claimed: false,
+            receipt_number: 0,
+            bond_paid: false,
+            last_deposit_slot: anonymous.last_deposit_slot,
+            dispute_raised: false,
+        };
+        let counted = record_deposit(
+            &mut ctx.accounts.campaign,
+            &mut scratch,
+            ctx.accounts.payer.key(),
+            0,
+            amount,
+            None,
+        )?;
+        // donor_count and contributed track Contribution accounts that can be
+        // paged through; the commitment only joins them once reveal_contribution
+        // creates one
+        let campaign = &mut ctx.accounts.campaign;
+        if anonymous.amount == 0 {
+            campaign.donor_count = campaign
+                .donor_count
+                .checked_sub(1)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+        campaign.contributed = campaign
+            .contributed
+            .checked_sub(counted)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        if anonymous.campaign == Pubkey::default() {
+            anonymous.campaign = ctx.accounts.campaign.key();
+            anonymous.commitment = commitment;
+            anonymous.first_deposited_at = Clock::get()?.unix_timestamp;
+            anonymous.bump = ctx.bumps.anonymous_deposit;
+            anonymous.receipt_number = u64::from(ctx.accounts.campaign.deposit_count);
+        }
+        anonymous.amount = scratch.amount;
+        anonymous.matched = scratch.matched;
+        anonymous.last_deposit_slot = scratch.last_deposit_slot;
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: ctx.accounts.vault_lamports.to_account_info(),
+                },
+            ),
+            counted,
+        )?;
+
+        Ok(())
+    }
+
+    /// Donor proves authorship of an anonymous deposit by revealing the salt
+    /// and moves it into their named `Contribution`
+    pub fn reveal_contribution(ctx: Context<RevealContribution>, salt: [u8; 32]) -> Result<()> {
+        let anonymous = &ctx.accounts.anonymous_deposit;
+        let donor = ctx.accounts.donor.key();
+        require!(
+            hashv(&[donor.as_ref(), &salt]).to_bytes() == anonymous.commitment,
+            ErrorCode::InvalidReveal
+        );
+
+        let contribution = &mut ctx.accounts.contribution;
+        let campaign = &mut ctx.accounts.campaign;
+        let first_backing = contribution.donor == Pubkey::default();
+        if first_backing {
+            contribution.campaign = anonymous.campaign;
+            contribution.donor = donor;
+            contribution.bump = ctx.bumps.contribution;
+            contribution.first_contributed_at = anonymous.first_deposited_at;
+            contribution.receipt_number = anonymous.receipt_number;
+            campaign.contribution_accounts = campaign
+                .contribution_accounts
+                .checked_add(1)
+                .ok_or(ErrorCode::MathOverflow)?;
+        } else {
+            contribution.first_contributed_at = contribution
+                .first_contributed_at
+                .min(anonymous.first_deposited_at);
+            contribution.receipt_number = contribution.receipt_number.min(anonymous.receipt_number);
+        }
+        contribution.last_deposit_slot = contribution
+            .last_deposit_slot
+            .max(anonymous.last_deposit_slot);
+
+        // Unrevealed commitments are not donors; the merge adds one unless the
+        // named contribution is already counted
+        if contribution.amount == 0 && anonymous.amount > 0 {
+            campaign.donor_count = campaign
+                .donor_count
+                .checked_add(1)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+
+        contribution.amount = contribution
+            .amount
+            .checked_add(anonymous.amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        campaign.contributed = campaign
+            .contributed
+            .checked_add(anonymous.amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        contribution.matched = contribution
+            .matched
+            .checked_add(anonymous.matched)
+            .ok_or(ErrorCode::MathOverflow)?;
+        // Once revealed the deposit is the donor's like any other
+        book_donor_profile(
+            &mut ctx.accounts.donor_profile,
+            donor,
+            ctx.bumps.donor_profile,
+            anonymous.amount,
+            first_backing,
+        )?;
+
+        Ok(())
+    }
+
+    /// Finalize that also creates the campaign's reward mint, so backers can
+    /// `claim_reward` tokens for their contributions afterwards. The mint is a
+    /// PDA and its own mint authority.
+    pub fn finalize_and_reward(ctx: Context<FinalizeAndReward>) -> Result<()> {
+        require!(ctx.accounts.campaign.reward_rate > 0, ErrorCode::NoRewards);
+        require!(
+            ctx.accounts.campaign.team.iter().all(|share| share.share_bps == 0),
+            ErrorCode::TeamCampaignRequiresVesting
+        );
+
+        let beneficiary = ctx.accounts.beneficiary.to_account_info();
+        settle(
+            Settlement {
+                campaign: &mut ctx.accounts.campaign,
+                vault_lamports: &ctx.accounts.vault_lamports,
+                vault_bump: ctx.bumps.vault_lamports,
+                authority: ctx.accounts.authority.to_account_info(),
+                global_stats: &mut ctx.accounts.global_stats,
+                creator_profile: &mut ctx.accounts.creator_profile,
+                protocol_config: ctx.accounts.protocol_config.to_account_info(),
+                treasury: ctx.accounts.treasury.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                cranker: None,
+                creates_reward_mint: true,
+            },
+            beneficiary,
+        )?;
+
+        let campaign_key = ctx.accounts.campaign.key();
+        let mint = ctx.accounts.reward_mint.to_account_info();
+        let mint_seeds: &[&[u8]] = &[
+            b"reward_mint",
+            campaign_key.as_ref(),
+            &[ctx.bumps.reward_mint],
+        ];
+
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::CreateAccount {
+                    from: ctx.accounts.beneficiary.to_account_info(),
+                    to: mint.clone(),
+                },
+                &[mint_seeds],
+            ),
+            Rent::get()?.minimum_balance(MINT_ACCOUNT_LEN as usize),
+            MINT_ACCOUNT_LEN,
+            &TOKEN_PROGRAM_ID,
+        )?;
+
+        // InitializeMint2 { decimals, mint_authority: mint, freeze_authority: None }
+        let mut data = Vec::with_capacity(35);
+        data.push(20);
+        data.push(REWARD_MINT_DECIMALS);
+        data.extend_from_slice(mint.key.as_ref());
+        data.push(0);
+        invoke_signed(
+            &Instruction {
+                program_id: TOKEN_PROGRAM_ID,
+                accounts: vec![AccountMeta::new(mint.key(), false)],
+                data,
+            },
+            std::slice::from_ref(&mint),
+            &[mint_seeds],
+        )?;
+
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.reward_mint_bump = ctx.bumps.reward_mint;
+        campaign.rewards_ready = true;
+
+        Ok(())
+    }
+
+    /// Backer mints `contribution.amount * reward_rate` reward tokens into a
+    /// token account of their choosing, once per contribution
+    pub fn claim_reward(ctx: Context<ClaimReward>) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+        require!(campaign.rewards_ready, ErrorCode::NoRewards);
+
+        let contribution = &mut ctx.accounts.contribution;
+        require!(!contribution.reward_claimed, ErrorCode::RewardAlreadyClaimed);
+        let amount = contribution
+            .amount
+            .checked_mul(campaign.reward_rate)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(amount > 0, ErrorCode::NothingToClaim);
+        contribution.reward_claimed = true;
+
+        let campaign_key = campaign.key();
+        let mint_seeds: &[&[u8]] = &[
+            b"reward_mint",
+            campaign_key.as_ref(),
+            &[campaign.reward_mint_bump],
+        ];
+        let mint = ctx.accounts.reward_mint.to_account_info();
+        let destination = ctx.accounts.destination.to_account_info();
+
+        // MintTo { amount }, signed by the mint as its own authority
+        let mut data = Vec::with_capacity(9);
+        data.push(7);
+        data.extend_from_slice(&amount.to_le_bytes());
+        invoke_signed(
+            &Instruction {
+                program_id: TOKEN_PROGRAM_ID,
+                accounts: vec![
+                    AccountMeta::new(mint.key(), false),
+                    AccountMeta::new(destination.key(), false),
+                    AccountMeta::new_readonly(mint.key(), true),
+                ],
+                data,
+            },
+            &[mint, destination],
+            &[mint_seeds],
+        )?;
+
+        Ok(())
+    }
+
+    /// Beneficiary draws part of the raised funds before finalize. Only once
+    /// `withdraw_floor` has been raised, and never below `withdraw_reserve`.
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(
+            campaign.allow_partial_withdrawals,
+            ErrorCode::WithdrawalsDisabled
+        );
+        require!(
+            campaign.team.iter().all(|share| share.share_bps == 0),
+            ErrorCode::TeamCampaignRequiresVesting
+        );
+        require!(
+            !campaign.overflow_in_progress,
+            ErrorCode::OverflowDistributionInProgress
+        );
+        require!(!campaign.dispute_active, ErrorCode::DisputeActive);
+        require!(
+            campaign.funds >= campaign.withdraw_floor,
+            ErrorCode::WithdrawFloorNotMet
+        );
+
+        let left = campaign
+            .funds
+            .checked_sub(amount)
+            .ok_or(ErrorCode::WithdrawReserveRequired)?;
+        require!(
+            left >= campaign.withdraw_reserve,
+            ErrorCode::WithdrawReserveRequired
+        );
+
+        let total_withdrawn = campaign
+            .total_withdrawn
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(
+            campaign.max_total_withdrawals == 0
+                || total_withdrawn <= campaign.max_total_withdrawals,
+            ErrorCode::WithdrawCapReached
+        );
+
+        campaign.funds = left;
+        campaign.total_withdrawn = total_withdrawn;
+
+        let campaign_key = campaign.key();
+        transfer_from_vault(
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.vault_lamports.to_account_info(),
+            &campaign_key,
+            ctx.bumps.vault_lamports,
+            &ctx.accounts.beneficiary.to_account_info(),
+            amount,
+        )
+    }
+
+    pub fn finalize(ctx: Context<Finalize>) -> Result<()> {
+        
+        // Ensure caller is the beneficiary (Anchor also checks via has_one + Signer)
+        require_keys_eq!(
+            ctx.accounts.beneficiary.key(),
+            ctx.accounts.campaign.beneficiary,
+            ErrorCode::Unauthorized
+        );
+        // Team payouts must go through the vesting flow
+        require!(
+            ctx.accounts.campaign.team.iter().all(|share| share.share_bps == 0),
+            ErrorCode::TeamCampaignRequiresVesting
+        );
+
+        let beneficiary = ctx.accounts.beneficiary.to_account_info();
+        settle(
+            Settlement {
+                campaign: &mut ctx.accounts.campaign,
+                vault_lamports: &ctx.accounts.vault_lamports,
+                vault_bump: ctx.bumps.vault_lamports,
+                authority: ctx.accounts.authority.to_account_info(),
+                global_stats: &mut ctx.accounts.global_stats,
+                creator_profile: &mut ctx.accounts.creator_profile,
+                protocol_config: ctx.accounts.protocol_config.to_account_info(),
+                treasury: ctx.accounts.treasury.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                cranker: None,
+                creates_reward_mint: false,
+            },
+            beneficiary,
+        )?;
+        Ok(())
+    }
+
+    /// Permissionless `finalize` for campaigns that reached their target. The
+    /// payout and fee go where `finalize` sends them; with `cranker_reward`
+    /// the vault's rent excess tips the cranker instead of the authority.
+    pub fn crank_finalize(ctx: Context<CrankFinalize>) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+        require!(raised(campaign)? >= campaign.target, ErrorCode::TargetNotReached);
+        require!(
+            campaign.team.iter().all(|share| share.share_bps == 0),
+            ErrorCode::TeamCampaignRequiresVesting
+        );
+
+        let cranker = campaign
+            .cranker_reward
+            .then(|| ctx.accounts.cranker.to_account_info());
+        let beneficiary = ctx.accounts.beneficiary.to_account_info();
+        settle(
+            Settlement {
+                campaign: &mut ctx.accounts.campaign,
+                vault_lamports: &ctx.accounts.vault_lamports,
+                vault_bump: ctx.bumps.vault_lamports,
+                authority: ctx.accounts.authority.to_account_info(),
+                global_stats: &mut ctx.accounts.global_stats,
+                creator_profile: &mut ctx.accounts.creator_profile,
+                protocol_config: ctx.accounts.protocol_config.to_account_info(),
+                treasury: ctx.accounts.treasury.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                cranker,
+                creates_reward_mint: false,
+            },
+            beneficiary,
+        )?;
+        Ok(())
+    }
+
+    /// Finalizes like `finalize` on the first call, then closes up to `count`
+    /// of the campaign's contribution accounts (passed as remaining accounts)
+    /// and returns their rent to the authority. Later calls only page through
+    /// the cleanup. Contributions with an unclaimed backer reward are skipped
+    /// until the reward is claimed.
+    pub fn finalize_and_cleanup<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FinalizeAndCleanup<'info>>,
+        count: u16,
+    ) -> Result<()> {
+        let children = ctx.remaining_accounts;
+        require!(
+            count > 0 && children.len() == usize::from(count),
+            ErrorCode::InvalidReceipt
+        );
+
+        if !ctx.accounts.campaign.is_finalized {
+            require!(
+                ctx.accounts.campaign.team.iter().all(|share| share.share_bps == 0),
+                ErrorCode::TeamCampaignRequiresVesting
+            );
+            let beneficiary = ctx.accounts.beneficiary.to_account_info();
+            settle(
+                Settlement {
+                    campaign: &mut ctx.accounts.campaign,
+                    vault_lamports: &ctx.accounts.vault_lamports,
+                    vault_bump: ctx.bumps.vault_lamports,
+                    authority: ctx.accounts.authority.to_account_info(),
+                    global_stats: &mut ctx.accounts.global_stats,
+                    creator_profile: &mut ctx.accounts.creator_profile,
+                    protocol_config: ctx.accounts.protocol_config.to_account_info(),
+                    treasury: ctx.accounts.treasury.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    cranker: None,
+                    creates_reward_mint: false,
+                },
+                beneficiary,
+            )?;
+        }
+
+        let campaign = &mut ctx.accounts.campaign;
+        let campaign_key = campaign.key();
+        let authority = ctx.accounts.authority.to_account_info();
+        for child in children {
+            require!(child.is_writable, ErrorCode::InvalidReceipt);
+            let contribution =
+                load_optional::<Contribution>(child)?.ok_or(ErrorCode::InvalidReceipt)?;
+            require_keys_eq!(contribution.campaign, campaign_key, ErrorCode::InvalidReceipt);
+
+            if campaign.rewards_ready && !contribution.reward_claimed && contribution.amount > 0 {
+                continue;
+            }
+            close_account(child, &authority)?;
+            campaign.children_closed = campaign
+                .children_closed
+                .checked_add(1)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+
+        if campaign.children_closed >= campaign.contribution_accounts {
+            campaign.fully_cleaned = true;
+        }
+        Ok(())
+    }
+
+    /// Fallback payout to the backup beneficiary once the primary has let
+    /// `deadline + backup_delay` pass without finalizing
+    pub fn finalize_to_backup(ctx: Context<FinalizeToBackup>) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+        require!(
+            campaign.deadline > 0 && campaign.backup_beneficiary != Pubkey::default(),
+            ErrorCode::NoBackupBeneficiary
+        );
+        let unlocks_at = campaign
+            .deadline
+            .checked_add(campaign.backup_delay)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(
+            Clock::get()?.unix_timestamp >= unlocks_at,
+            ErrorCode::BackupNotYetAllowed
+        );
+        // The whitelist is checked at payout, so it also covers a backup named
+        // before the protocol started enforcing it
+        require_beneficiary_allowed(
+            &ctx.accounts.protocol_config,
+            &ctx.accounts.beneficiary_registry,
+        )?;
+
+        let backup = ctx.accounts.backup_beneficiary.to_account_info();
+        settle(
+            Settlement {
+                campaign: &mut ctx.accounts.campaign,
+                vault_lamports: &ctx.accounts.vault_lamports,
+                vault_bump: ctx.bumps.vault_lamports,
+                authority: ctx.accounts.authority.to_account_info(),
+                global_stats: &mut ctx.accounts.global_stats,
+                creator_profile: &mut ctx.accounts.creator_profile,
+                protocol_config: ctx.accounts.protocol_config.to_account_info(),
+                treasury: ctx.accounts.treasury.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                cranker: None,
+                creates_reward_mint: false,
+            },
+            backup,
+        )?;
+        Ok(())
+    }
+
+    /// Finalizes a team campaign into a vesting account instead of paying the
+    /// beneficiary. Each member's split unlocks linearly over `duration`
+    /// seconds, starting once `cliff` seconds have passed.
+    pub fn finalize_team_vested(
+        ctx: Context<FinalizeTeamVested>,
+        cliff: i64,
+        duration: i64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.campaign.team.iter().any(|share| share.share_bps > 0),
+            ErrorCode::NoTeam
+        );
+        require!(
+            duration > 0 && (0..=duration).contains(&cliff),
+            ErrorCode::InvalidVestingSchedule
+        );
+
+        let team_vesting = ctx.accounts.team_vesting.to_account_info();
+        let total = settle(
+            Settlement {
+                campaign: &mut ctx.accounts.campaign,
+                vault_lamports: &ctx.accounts.vault_lamports,
+                vault_bump: ctx.bumps.vault_lamports,
+                authority: ctx.accounts.authority.to_account_info(),
+                global_stats: &mut ctx.accounts.global_stats,
+                creator_profile: &mut ctx.accounts.creator_profile,
+                protocol_config: ctx.accounts.protocol_config.to_account_info(),
+                treasury: ctx.accounts.treasury.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                cranker: None,
+                creates_reward_mint: false,
+            },
+            team_vesting,
+        )?;
+
+        let vesting = &mut ctx.accounts.team_vesting;
+        vesting.campaign = ctx.accounts.campaign.key();
+        vesting.total = total;
+        vesting.start = ctx.accounts.campaign.finalized_at;
+        vesting.cliff = cliff;
+        vesting.duration = duration;
+        vesting.team = ctx.accounts.campaign.team;
+        vesting.bump = ctx.bumps.team_vesting;
+
+        Ok(())
+    }
+
+    /// Team member withdraws whatever of their split has vested so far. The
+    /// member's stream account is created on their first claim.
+    pub fn claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
+        let vesting = &ctx.accounts.team_vesting;
+        let member = ctx.accounts.member.key();
+        let allocation = team_allocation(vesting, member)?;
+        // Every member is a payout address, so each one is held to the
+        // beneficiary whitelist when they claim
+        require_beneficiary_allowed(
+            &ctx.accounts.protocol_config,
+            &ctx.accounts.beneficiary_registry,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let elapsed = now.saturating_sub(vesting.start);
+        let vested = if elapsed < vesting.cliff {
+            0
+        } else if elapsed >= vesting.duration {
+            allocation
+        } else {
+            let vested = u128::from(allocation) * elapsed as u128 / vesting.duration as u128;
+            u64::try_from(vested).map_err(|_| ErrorCode::MathOverflow)?
+        };
+
+        let stream = &mut ctx.accounts.vesting_stream;
+        if stream.member == Pubkey::default() {
+            stream.team_vesting = vesting.key();
+            stream.member = member;
+            stream.bump = ctx.bumps.vesting_stream;
+        }
+
+        let claimable = vested.saturating_sub(stream.claimed);
+        require!(claimable > 0, ErrorCode::NothingToClaim);
+        stream.claimed += claimable;
+
+        ctx.accounts.team_vesting.sub_lamports(claimable)?;
+        ctx.accounts.member.add_lamports(claimable)?;
+
+        Ok(())
+    }
+
+    /// One-time setup of the protocol admin, restricted to the program's
+    /// upgrade authority
+    pub fn init_protocol_config(ctx: Context<InitProtocolConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.protocol_config;
+        config.admin = ctx.accounts.admin.key();
+        config.enforce_beneficiary_whitelist = false;
+        config.bump = ctx.bumps.protocol_config;
+        config.treasury = ctx.accounts.admin.key();
+        config.rent_split_bps = 0;
+        config.max_active_per_creator = 0;
+
+        Ok(())
+    }
+
+    /// Admin turns the beneficiary whitelist on or off
+    pub fn set_beneficiary_whitelist(
+        ctx: Context<UpdateProtocolConfig>,
+        enforce: bool,
+    ) -> Result<()> {
+        ctx.accounts.protocol_config.enforce_beneficiary_whitelist = enforce;
+        Ok(())
+    }
+
+    /// Admin sets the protocol treasury and its share of the vault remainder
+    pub fn set_rent_split(
+        ctx: Context<UpdateProtocolConfig>,
+        treasury: Pubkey,
+        rent_split_bps: u16,
+    ) -> Result<()> {
+        require!(
+            rent_split_bps as u64 <= BPS_DENOMINATOR,
+            ErrorCode::InvalidRentSplit
+        );
+        let config = &mut ctx.accounts.protocol_config;
+        config.treasury = treasury;
+        config.rent_split_bps = rent_split_bps;
+        Ok(())
+    }
+
+    /// Admin caps how many campaigns one creator may have open (0 for no cap)
+    pub fn set_max_active_per_creator(
+        ctx: Context<UpdateProtocolConfig>,
+        max_active_per_creator: u32,
+    ) -> Result<()> {
+        ctx.accounts.protocol_config.max_active_per_creator = max_active_per_creator;
+        Ok(())
+    }
+
+    /// Admin adds a beneficiary to the registry or revokes it
+    pub fn set_beneficiary_allowed(
+        ctx: Context<SetBeneficiaryAllowed>,
+        beneficiary: Pubkey,
+        allowed: bool,
+    ) -> Result<()> {
+        let entry = &mut ctx.accounts.beneficiary_registry;
+        entry.beneficiary = beneficiary;
+        entry.allowed = allowed;
+        entry.bump = ctx.bumps.beneficiary_registry;
+        Ok(())
+    }
+
+    /// Authority retunes the finalize fee before anything has been raised
+    pub fn update_fee(ctx: Context<UpdateFee>, new_fee_bps: u16) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        require!(new_fee_bps as u64 <= BPS_DENOMINATOR, ErrorCode::InvalidFee);
+        require!(raised(campaign)? == 0, ErrorCode::FeeLocked);
+        campaign.fee_bps = new_fee_bps;
+        Ok(())
+    }
+
+    /// Authority replaces the refund policy, unless it was made immutable
+    pub fn update_refund_policy(
+        ctx: Context<UpdateRefundPolicy>,
+        policy: RefundPolicy,
+    ) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        require!(!campaign.refund_policy.immutable, ErrorCode::RefundPolicyImmutable);
+        validate_refund_policy(&policy)?;
+        campaign.refund_policy = policy;
+        Ok(())
+    }
+
+    /// Authority moves the target before finalize. Funds above the new
+    /// ceiling are returned to donors through `distribute_overflow`.
+    pub fn update_target(ctx: Context<UpdateTarget>, target: u64) -> Result<()> {
+        let campaign = &mut ctx.accounts.campaign;
+        validate_campaign_config(
+            target,
+            &campaign_config(campaign),
+            Clock::get()?.unix_timestamp,
+        )?;
+        campaign.target = target;
+        campaign.overflow_pending = campaign.funds > deposit_ceiling(campaign)?;
+        Ok(())
+    }
+
+    /// Authority points the campaign at a new beneficiary before finalize
+    pub fn update_beneficiary(ctx: Context<UpdateBeneficiary>) -> Result<()> {
+        require_beneficiary_allowed(
+            &ctx.accounts.protocol_config,
+            &ctx.accounts.beneficiary_registry,
+        )?;
+
+        ctx.accounts.campaign.beneficiary = ctx.accounts.new_beneficiary.key();
+        Ok(())
+    }
+
+    /// Break-glass recovery of lamports stranded in a vault whose campaign
+    /// account has already been closed
+    pub fn emergency_reclaim(ctx: Context<EmergencyReclaim>) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+        require!(
+            campaign.owner == &system_program::ID && campaign.data_is_empty(),
+            ErrorCode::CampaignStillOpen
+        );
+
+        let amount = ctx.accounts.vault_lamports.lamports();
+        require!(amount > 0, ErrorCode::NothingToReclaim);
+
+        let campaign_key = campaign.key();
+        let vault_lamports_seeds: &[&[u8]] = &[
+            b"vault_lamports",
+            campaign_key.as_ref(),
+            &[ctx.bumps.vault_lamports],
+        ];
+
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.vault_lamports.to_account_info(),
+                    to: ctx.accounts.recovery.to_account_info(),
+                },
+                &[vault_lamports_seeds],
+            ),
+            amount,
+        )?;
+
+        msg!(
+            "EMERGENCY RECLAIM: {} lamports from vault of closed campaign {} to {}",
+            amount,
+            campaign_key,
+            ctx.accounts.recovery.key()
+        );
+        emit!(EmergencyReclaimed {
+            campaign: campaign_key,
+            vault: ctx.accounts.vault_lamports.key(),
+            recovery: ctx.accounts.recovery.key(),
+            admin: ctx.accounts.admin.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Keeps a compact record of a finalized campaign and closes the campaign
+    /// account, returning its rent to the authority
+    pub fn archive(ctx: Context<Archive>) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+
+        let archived = &mut ctx.accounts.archived_campaign;
+        archived.campaign = campaign.key();
+        archived.authority = campaign.authority;
+        archived.campaign_id = campaign.campaign_id;
+        archived.final_funds = campaign.final_funds;
+        archived.donor_count = campaign.donor_count;
+        archived.created_at = campaign.created_at;
+        archived.finalized_at = campaign.finalized_at;
+        archived.state_hash = campaign_state_hash(campaign)?;
+        archived.bump = ctx.bumps.archived_campaign;
+
+        Ok(())
+    }
+
+    /// Permissionless check that the campaign's books balance. Contributions
+    /// passed as remaining accounts are summed and checked against `funds`.
+    /// A violation is emitted as an event before the instruction fails.
+    pub fn assert_invariants(ctx: Context<AssertInvariants>) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+        let vault_balance = ctx.accounts.vault_lamports.lamports();
+        let rent_reserve = Rent::get()?.minimum_balance(0);
+
+        let mut receipts_total: u64 = 0;
+        for info in ctx.remaining_accounts.iter() {
+            let contribution =
+                load_optional::<Contribution>(info)?.ok_or(ErrorCode::InvalidReceipt)?;
+            require_keys_eq!(contribution.campaign, campaign.key(), ErrorCode::InvalidReceipt);
+            receipts_total = receipts_total
+                .checked_add(contribution.amount)
+                .and_then(|total| total.checked_add(contribution.matched))
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+
+        let required_vault = campaign
+            .funds
+            .checked_add(campaign.match_pool)
+            .and_then(|total| total.checked_add(rent_reserve))
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        // The vault is closed on finalize, so its balance only matters while open
+        let violation = if !campaign.is_finalized && vault_balance < required_vault {
+            Some((INVARIANT_VAULT_UNDERFUNDED, required_vault, vault_balance))
+        } else if campaign.funds > deposit_ceiling(campaign)?
+            // A lowered target is the one sanctioned way above the ceiling
+            && !campaign.overflow_pending
+            && !campaign.overflow_in_progress
+        {
+            Some((INVARIANT_FUNDS_ABOVE_TARGET, deposit_ceiling(campaign)?, campaign.funds))
+        } else if campaign.is_finalized && campaign.funds > 0 {
+            Some((INVARIANT_FINALIZED_WITH_FUNDS, 0, campaign.funds))
+        } else if !campaign.is_finalized && receipts_total > raised(campaign)? {
+            Some((INVARIANT_RECEIPTS_EXCEED_FUNDS, raised(campaign)?, receipts_total))
+        } else {
+            None
+        };
+
+        if let Some((code, expected, actual)) = violation {
+            emit!(InvariantViolation {
+                campaign: campaign.key(),
+                code,
+                expected,
+                actual,
+            });
+            return err!(ErrorCode::InvariantViolated);
+        }
+
+        emit!(InvariantsOk {
+            campaign: campaign.key(),
+            vault_balance,
+            funds: campaign.funds,
+            match_pool: campaign.match_pool,
+            target: campaign.target,
+            rent_reserve,
+            receipts_total,
+        });
+
+        Ok(())
+    }
+
+    /// Actual vault balance, including rent and any lamports sent outside of
+    /// `deposit`, for reconciling against `funds`
+    pub fn vault_balance(ctx: Context<ReadVault>) -> Result<u64> {
+        Ok(ctx.accounts.vault_lamports.lamports())
+    }
+
+    /// Whether the campaign still hashes to `expected_hash` (SHA-256 of its
+    /// serialized state, as recorded by `archive`). A mismatch is reported, not
+    /// raised, so monitors can act on it.
+    pub fn verify_snapshot(ctx: Context<ReadCampaign>, expected_hash: [u8; 32]) -> Result<bool> {
+        Ok(campaign_state_hash(&ctx.accounts.campaign)? == expected_hash)
+    }
+
+    /// Campaign state as `[STATE_EXPORT_VERSION, borsh(Campaign)...]` for
+    /// migrating to a redeployed program with `import_state`
+    pub fn export_state(ctx: Context<ReadCampaign>) -> Result<Vec<u8>> {
+        let mut blob = Vec::with_capacity(1 + Campaign::INIT_SPACE);
+        blob.push(STATE_EXPORT_VERSION);
+        ctx.accounts.campaign.serialize(&mut blob)?;
+        Ok(blob)
+    }
+
+    /// Whole campaign (config and state) for clients in one call: the version
+    /// byte, the payload length as u16 LE, then the Borsh-serialized campaign.
+    /// The length lets a client that sees an unknown version skip or report
+    /// the blob without parsing it.
+    pub fn full_state(ctx: Context<ReadCampaign>) -> Result<Vec<u8>> {
+        let mut payload = Vec::with_capacity(Campaign::INIT_SPACE);
+        ctx.accounts.campaign.serialize(&mut payload)?;
+        let len = u16::try_from(payload.len()).map_err(|_| ErrorCode::MathOverflow)?;
+
+        let mut blob = Vec::with_capacity(3 + payload.len());
+        blob.push(FULL_STATE_VERSION);
+        blob.extend_from_slice(&len.to_le_bytes());
+        blob.extend_from_slice(&payload);
+        Ok(blob)
+    }
+
+    /// Loads an `export_state` blob into a freshly initialized campaign. The
+    /// new campaign keeps its own id and authority (its PDA seeds); the vault
+    /// must already hold the imported match pool. The imported settings pass
+    /// the same checks as `initialize`.
+    pub fn import_state(ctx: Context<ImportState>, blob: Vec<u8>) -> Result<()> {
+        let (&version, data) = blob.split_first().ok_or(ErrorCode::InvalidStateBlob)?;
+        require!(
+            version == STATE_EXPORT_VERSION,
+            ErrorCode::UnsupportedStateVersion
+        );
+        let mut imported =
+            Campaign::try_from_slice(data).map_err(|_| ErrorCode::InvalidStateBlob)?;
+        require!(
+            !imported.is_finalized && !imported.is_cancelled && !imported.is_failed,
+            ErrorCode::ImportNotOpen
+        );
+        // Deposits live in the source campaign's Contribution accounts, which
+        // cannot follow it, so they would be left without a refund path
+        require!(
+            imported.funds == 0
+                && imported.donor_count == 0
+                && imported.contribution_accounts == 0
+                && imported.contributed == 0,
+            ErrorCode::ImportHasDeposits
+        );
+        require_keys_eq!(
+            ctx.accounts.beneficiary.key(),
+            imported.beneficiary,
+            ErrorCode::ImportBeneficiaryMismatch
+        );
+        validate_campaign_config(
+            imported.target,
+            &campaign_config(&imported),
+            Clock::get()?.unix_timestamp,
+        )?;
+        require_beneficiary_allowed(
+            &ctx.accounts.protocol_config,
+            &ctx.accounts.beneficiary_registry,
+        )?;
+
+        let campaign = &mut ctx.accounts.campaign;
+        require!(
+            campaign.funds == 0
+                && campaign.donor_count == 0
+                && campaign.match_pool == 0
+                && campaign.total_withdrawn == 0
+                && campaign.total_streamed == 0,
+            ErrorCode::CampaignNotFresh
+        );
+
+        let rent_reserve = Rent::get()?.minimum_balance(0);
+        let required_vault = imported
+            .match_pool
+            .checked_add(rent_reserve)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(
+            ctx.accounts.vault_lamports.lamports() >= required_vault,
+            ErrorCode::ImportUnderfunded
+        );
+
+        imported.campaign_id = campaign.campaign_id;
+        imported.authority = campaign.authority;
+        // Bonds live in per-campaign PDAs: the source's stays behind with the
+        // source, and the destination keeps the one escrowed for it
+        imported.bond_amount = campaign.bond_amount;
+        imported.bond_state = campaign.bond_state;
+        imported.bond_basis = campaign.bond_basis;
+        imported.bond_distributed = campaign.bond_distributed;
+        imported.bond_processed = campaign.bond_processed;
+        campaign.set_inner(imported);
+
+        Ok(())
+    }
+
+    /// Time left to the deadline as a coarse bucket for "ending soon" filters.
+    /// Campaigns without a deadline report the longest bucket.
+    pub fn urgency_bucket(ctx: Context<ReadCampaign>) -> Result<u8> {
+        let deadline = ctx.accounts.campaign.deadline;
+        if deadline == 0 {
+            return Ok(URGENCY_LONGER);
+        }
+
+        let left = deadline.saturating_sub(Clock::get()?.unix_timestamp);
+        Ok(match left {
+            i64::MIN..=0 => URGENCY_EXPIRED,
+            _ if left < SECONDS_PER_DAY => URGENCY_UNDER_DAY,
+            _ if left < 7 * SECONDS_PER_DAY => URGENCY_UNDER_WEEK,
+            _ if left < 30 * SECONDS_PER_DAY => URGENCY_UNDER_MONTH,
+            _ => URGENCY_LONGER,
+        })
+    }
+
+    /// Composite 0-100 ranking score: progress 40%, velocity 30%, backers 20%,
+    /// time left 10%. Velocity compares what was raised with the straight-line
+    /// pace to the deadline; without a deadline it falls back to progress and
+    /// the time component is full. Cancelled and failed campaigns score 0.
+    pub fn health_score(ctx: Context<ReadCampaign>) -> Result<u8> {
+        let campaign = &ctx.accounts.campaign;
+        if campaign.is_cancelled || campaign.is_failed {
+            return Ok(0);
+        }
+
+        // Finalized campaigns are scored on what they raised before the payout
+        let counted = if campaign.is_finalized {
+            campaign.final_funds
+        } else {
+            campaign.funds
+        };
+        let raised = counted
+            .checked_add(campaign.total_withdrawn)
+            .and_then(|total| total.checked_add(campaign.total_streamed))
+            .ok_or(ErrorCode::MathOverflow)?;
+        let progress = percent(raised, campaign.target);
+
+        let now = Clock::get()?.unix_timestamp;
+        let span = campaign.deadline.saturating_sub(campaign.created_at);
+        let (velocity, time) = if campaign.deadline == 0 || span <= 0 {
+            (progress, 100)
+        } else {
+            let elapsed = now.saturating_sub(campaign.created_at).clamp(0, span);
+            let expected = u128::from(campaign.target) * elapsed as u128 / span as u128;
+            let velocity = if expected == 0 {
+                100
+            } else {
+                percent(raised, u64::try_from(expected).map_err(|_| ErrorCode::MathOverflow)?)
+            };
+            (velocity, percent((span - elapsed) as u64, span as u64))
+        };
+        let backers = percent(u64::from(campaign.donor_count), HEALTH_FULL_BACKERS);
+
+        let score = (HEALTH_WEIGHT_PROGRESS * progress
+            + HEALTH_WEIGHT_VELOCITY * velocity
+            + HEALTH_WEIGHT_BACKERS * backers
+            + HEALTH_WEIGHT_TIME * time)
+            / 100;
+        Ok(score as u8)
+    }
+
+    /// What `finalize` would pay the beneficiary right now: the accounted funds
+    /// less the fee, or nothing for a dust sweep. Streamed lamports already
+    /// reached the beneficiary and the vault rent goes to the authority, so
+    /// neither is counted. Zero whenever `finalize` (`finalize_and_reward` for
+    /// reward campaigns) would fail.
+    pub fn net_to_beneficiary(ctx: Context<ReadCampaign>) -> Result<u64> {
+        let campaign = &ctx.accounts.campaign;
+        let team = campaign.team.iter().any(|share| share.share_bps > 0);
+        if campaign.is_finalized
+            || campaign.is_cancelled
+            || campaign.is_failed
+            || campaign.dispute_active
+            || campaign.overflow_in_progress
+            || team
+            || campaign.funds == 0
+        {
+            return Ok(0);
+        }
+        if campaign.strict_target && raised(campaign)? != campaign.target {
+            return Ok(0);
+        }
+
+        let (dust, _, payout) = payout_split(campaign)?;
+        if !dust && campaign.deposit_count < campaign.min_deposit_count {
+            return Ok(0);
+        }
+        Ok(payout)
+    }
+
+    /// 0-100 trust signal for the campaign's creator: success ratio over the
+    /// creator's settled campaigns 70%, lamports raised by the successful ones
+    /// 30%. A creator with nothing settled yet scores 0.
+    pub fn creator_reputation(ctx: Context<ReadCreatorProfile>) -> Result<u8> {
+        let profile = &ctx.accounts.creator_profile;
+        let settled = profile
+            .campaigns_succeeded
+            .checked_add(profile.campaigns_failed)
+            .ok_or(ErrorCode::MathOverflow)?;
+        if settled == 0 {
+            return Ok(0);
+        }
+
+        let success = percent(u64::from(profile.campaigns_succeeded), u64::from(settled));
+        let volume = percent(profile.total_raised, REPUTATION_FULL_VOLUME);
+        let score =
+            (REPUTATION_WEIGHT_SUCCESS * success + REPUTATION_WEIGHT_VOLUME * volume) / 100;
+        Ok(score as u8)
+    }
+
+    /// Lamports the donor has put in through `deposit` across all campaigns;
+    /// 0 for a donor without a profile
+    pub fn donor_reputation(ctx: Context<ReadDonorProfile>) -> Result<u64> {
+        Ok(load_optional::<DonorProfile>(&ctx.accounts.donor_profile)?
+            .map_or(0, |profile| profile.total_contributed))
+    }
+
+    /// Most this donor can still deposit: what is left to the target, further
+    /// limited by the per-donor cap. Zero once the campaign stops taking deposits.
+    pub fn donor_remaining(ctx: Context<ReadContribution>) -> Result<u64> {
+        let campaign = &ctx.accounts.campaign;
+        let past_deadline = campaign.deadline > 0
+            && Clock::get()?.unix_timestamp >= campaign.deadline
+            && campaign.end_action != END_ACTION_KEEP_OPEN;
+        if campaign.is_finalized || campaign.is_cancelled || campaign.is_failed || past_deadline {
+            return Ok(0);
+        }
+
+        let contributed = load_optional::<Contribution>(&ctx.accounts.contribution)?
+            .map_or(0, |contribution| contribution.amount);
+        let campaign_remaining = deposit_ceiling(campaign)?.saturating_sub(raised(campaign)?);
+
+        Ok(campaign_remaining.min(donor_cap_left(campaign, contributed)))
+    }
+
+    /// Hands the gated content commitment to donors whose contribution reaches
+    /// the unlock threshold. A zero threshold still needs a contribution.
+    pub fn unlock_content(ctx: Context<UnlockContent>) -> Result<[u8; 32]> {
+        let campaign = &ctx.accounts.campaign;
+        let contributed = load_optional::<Contribution>(&ctx.accounts.contribution)?
+            .map_or(0, |contribution| contribution.amount);
+        require!(
+            contributed > 0 && contributed >= campaign.unlock_threshold,
+            ErrorCode::ContentLocked
+        );
+        Ok(campaign.gated_content_hash)
+    }
+
+    /// Donor appends a comment to the campaign's hash chain. Only the hash is
+    /// stored; each link is sha256(previous link, comment_hash, donor).
+    pub fn post_comment(ctx: Context<PostComment>, comment_hash: [u8; 32]) -> Result<()> {
+        let donor = ctx.accounts.donor.key();
+        let campaign = &mut ctx.accounts.campaign;
+
+        let chain_hash =
+            hashv(&[&campaign.last_comment_hash, &comment_hash, donor.as_ref()]).to_bytes();
+        campaign.last_comment_hash = chain_hash;
+        campaign.comment_count = campaign
+            .comment_count
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        emit!(CommentPosted {
+            campaign: campaign.key(),
+            donor,
+            comment_hash,
+            chain_hash,
+            index: campaign.comment_count,
+        });
+        Ok(())
+    }
+
+    /// Contribution amounts for the accounts passed in `remaining_accounts`,
+    /// in order. Anything that is not this campaign's contribution PDA reads
+    /// as 0.
+    pub fn contributions_of(ctx: Context<ReadCampaign>) -> Result<Vec<u64>> {
+        require!(
+            ctx.remaining_accounts.len() <= MAX_CONTRIBUTIONS_QUERY,
+            ErrorCode::TooManyAccounts
+        );
+        let campaign_key = ctx.accounts.campaign.key();
+
+        let amounts = ctx
+            .remaining_accounts
+            .iter()
+            .map(|info| {
+                // Foreign, closed or other-type accounts fail to load and read as 0
+                let Ok(Some(contribution)) = load_optional::<Contribution>(info) else {
+                    return 0;
+                };
+                let expected = Pubkey::create_program_address(
+                    &[
+                        b"contribution",
+                        campaign_key.as_ref(),
+                        contribution.donor.as_ref(),
+                        &[contribution.bump],
+                    ],
+                    &crate::ID,
+                );
+                let genuine =
+                    contribution.campaign == campaign_key && expected.ok() == Some(info.key());
+                if genuine {
+                    contribution.amount
+                } else {
+                    0
+                }
+            })
+            .collect();
+        Ok(amounts)
+    }
+
+    /// Receipt number of the donor's first deposit; 0 when they never contributed
+    pub fn get_receipt_number(ctx: Context<ReadContribution>) -> Result<u64> {
+        Ok(load_optional::<Contribution>(&ctx.accounts.contribution)?
+            .map_or(0, |contribution| contribution.receipt_number))
+    }
+
+    /// Whether the donor's first contribution happened at or before `cutoff`.
+    /// Donors without a contribution (or with nothing left in it) return false.
+    pub fn contributed_before(ctx: Context<ReadContribution>, cutoff: i64) -> Result<bool> {
+        let Some(contribution) = load_optional::<Contribution>(&ctx.accounts.contribution)? else {
+            return Ok(false);
+        };
+
+        Ok(contribution.amount > 0 && contribution.first_contributed_at <= cutoff)
+    }
+
+}
+
+/// Only `deposit` carries the beneficiary and social attestation accounts, so
+/// streaming and social-gated campaigns take deposits through it alone
+fn require_side_deposit_allowed(campaign: &Campaign) -> Result<()> {
+    require!(!campaign.stream_payout, ErrorCode::StreamingDepositOnly);
+    require!(!campaign.require_social, ErrorCode::SocialRequired);
+    Ok(())
+}
+
+/// Social-gated campaigns only take deposits from donors holding a live
+/// attestation from the campaign's trusted issuer
+fn require_social_attestation(
+    campaign: &Campaign,
+    attestation: &AccountInfo,
+    donor: &Pubkey,
+) -> Result<()> {
+    if !campaign.require_social {
+        return Ok(());
+    }
+    let attestation =
+        load_optional::<SocialAttestation>(attestation)?.ok_or(ErrorCode::SocialRequired)?;
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        attestation.issuer == campaign.social_issuer
+            && attestation.subject == *donor
+            && (attestation.expires_at == 0 || now < attestation.expires_at),
+        ErrorCode::SocialRequired
+    );
+    Ok(())
+}
+
+/// Deposits above the campaign's threshold must go through the
+/// request/confirm flow
+fn require_below_large_deposit_threshold(campaign: &Campaign, amount: u64) -> Result<()> {
+    require!(
+        campaign.large_deposit_threshold == 0 || amount <= campaign.large_deposit_threshold,
+        ErrorCode::DepositRequiresConfirmation
+    );
+    Ok(())
+}
+
+/// In round-up mode a deposit below a tier becomes the smallest tier above it,
+/// as long as the donor can pay for it and it fits under the campaign and donor
+/// caps. Otherwise the exact amount goes through unchanged.
+fn round_up_to_tier(
+    campaign: &Account<Campaign>,
+    contribution: &Contribution,
+    donor: &AccountInfo,
+    amount: u64,
+) -> Result<u64> {
+    if !campaign.round_up_to_tier {
+        return Ok(amount);
+    }
+    let Some(tier) = campaign
+        .allowed_amounts
+        .iter()
+        .copied()
+        .filter(|&tier| tier > amount)
+        .min()
+    else {
+        return Ok(amount);
+    };
+
+    let available = donor
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(0));
+    let remaining = deposit_ceiling(campaign)?.saturating_sub(raised(campaign)?);
+    if tier > available
+        || tier > remaining
+        || tier > donor_cap_left(campaign, contribution.amount)
+    {
+        return Ok(amount);
+    }
+
+    emit!(DepositRoundedUp {
+        campaign: campaign.key(),
+        donor: donor.key(),
+        requested: amount,
+        rounded: tier,
+    });
+    Ok(tier)
+}
+
+/// Books a deposit of up to `amount` from `donor` against the campaign and the
+/// donor's contribution. Returns the lamports the caller must move into the vault.
+fn record_deposit(
+    campaign: &mut Account<Campaign>,
+    contribution: &mut Contribution,
+    donor: Pubkey,
+    contribution_bump: u8,
+    amount: u64,
+    donor_profile: Option<(&mut DonorProfile, u8)>,
+) -> Result<u64> {
+    require!(amount > 0, ErrorCode::InvalidAmount);
+    require!(
+        !(campaign.block_beneficiary_deposits && donor == campaign.beneficiary),
+        ErrorCode::BeneficiaryCannotDonate
+    );
+    require!(!campaign.is_finalized, ErrorCode::CampaignFinalized);
+    require!(!campaign.is_cancelled, ErrorCode::CampaignCancelled);
+    require!(!campaign.is_failed, ErrorCode::CampaignFailed);
+    require!(!campaign.is_paused, ErrorCode::CampaignPaused);
+    require!(
+        !campaign.overflow_in_progress,
+        ErrorCode::OverflowDistributionInProgress
+    );
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        campaign.deadline == 0
+            || now < campaign.deadline
+            || campaign.end_action == END_ACTION_KEEP_OPEN,
+        ErrorCode::DeadlinePassed
+    );
+    // Slots cannot be nudged by validators the way timestamps can. The first
+    // deposit has no slot recorded; anonymous deposits are gapped per commitment.
+    let slot = Clock::get()?.slot;
+    require!(
+        campaign.min_slot_gap == 0
+            || contribution.last_deposit_slot == 0
+            || slot >= contribution.last_deposit_slot.saturating_add(campaign.min_slot_gap),
+        ErrorCode::DonationTooSoon
+    );
+
+    // Remaining amount to reach the (tolerance-adjusted) target
+    let remaining = deposit_ceiling(campaign)?
+        .checked_sub(raised(campaign)?)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    require!(remaining > 0, ErrorCode::TargetAlreadyReached);
+
+    let donor_left = donor_cap_left(campaign, contribution.amount);
+    require!(donor_left > 0, ErrorCode::DonorCapReached);
+
+    // Fixed-tier campaigns take exact amounts only, so there is nothing to clamp.
+    // In round-up mode the tiers are only targets to round towards.
+    if !campaign.round_up_to_tier && campaign.allowed_amounts.iter().any(|&tier| tier > 0) {
+        require!(campaign.allowed_amounts.contains(&amount), ErrorCode::InvalidTier);
+        require!(amount <= remaining, ErrorCode::TierExceedsRemaining);
+        require!(amount <= donor_left, ErrorCode::DonorCapReached);
+    }
+
+    // "counted" amount: we only accept up to remaining
+    let mut counted = amount.min(remaining);
+
+    if counted < amount {
+        msg!(
+            "Deposit amount reduced from {} to {} to avoid exceeding target",
+            amount,
+            counted
+        );
+    }
+    if counted > donor_left {
+        msg!(
+            "Deposit amount reduced from {} to {} by the per-donor cap",
+            counted,
+            donor_left
+        );
+        counted = donor_left;
+    }
+
+    if counted < amount {
+        emit!(DepositClamped {
+            campaign: campaign.key(),
+            donor,
+            requested: amount,
+            accepted: counted,
+        });
+    }
+
+    campaign.deposit_count = campaign
+        .deposit_count
+        .checked_add(1)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    // Match the part of the donor's cumulative contribution that this deposit
+    // covers, limited by the match pool and what is still left to the target
+    let first_backing = contribution.donor == Pubkey::default();
+    if first_backing {
+        contribution.campaign = campaign.key();
+        contribution.donor = donor;
+        contribution.bump = contribution_bump;
+        contribution.first_contributed_at = now;
+        contribution.receipt_number = u64::from(campaign.deposit_count);
+        campaign.contribution_accounts = campaign
+            .contribution_accounts
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+    }
+
+    let before = contribution.amount;
+    if before == 0 {
+        campaign.donor_count = campaign
+            .donor_count
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+    }
+    let after = before.checked_add(counted).ok_or(ErrorCode::MathOverflow)?;
+    let matched = match_for_range(&campaign.match_tiers, before, after)?
+        .min(campaign.match_pool)
+        .min(remaining - counted);
+
+    if matched > 0 {
+        msg!("Deposit of {} matched with {}", counted, matched);
+    }
+
+    contribution.amount = after;
+    campaign.contributed = campaign
+        .contributed
+        .checked_add(counted)
+        .ok_or(ErrorCode::MathOverflow)?;
+    contribution.last_deposit_slot = slot;
+    contribution.matched = contribution
+        .matched
+        .checked_add(matched)
+        .ok_or(ErrorCode::MathOverflow)?;
+    campaign.match_pool -= matched;
+    campaign.total_matched = campaign
+        .total_matched
+        .checked_add(matched)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    // Update accounted funds
+    campaign.funds = campaign
+        .funds
+        .checked_add(counted)
+        .and_then(|funds| funds.checked_add(matched))
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    // Stable line for log scrapers: DEPOSIT|campaign|donor|counted|funds
+    if campaign.verbose_logs {
+        msg!("DEPOSIT|{}|{}|{}|{}", campaign.key(), donor, counted, campaign.funds);
+    }
+
+    // Every path into a named contribution books it on the donor's profile, so
+    // a later refund always takes back lamports the profile counted
+    if let Some((profile, bump)) = donor_profile {
+        book_donor_profile(profile, donor, bump, counted, first_backing)?;
+    }
+
+    Ok(counted)
+}
+
+/// Adds a contribution to the donor's cross-campaign profile, creating the
+/// profile on first use
+fn book_donor_profile(
+    profile: &mut DonorProfile,
+    donor: Pubkey,
+    bump: u8,
+    amount: u64,
+    first_backing: bool,
+) -> Result<()> {
+    profile.donor = donor;
+    profile.bump = bump;
+    profile.total_contributed = profile
+        .total_contributed
+        .checked_add(amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+    if first_backing {
+        profile.campaigns_backed = profile
+            .campaigns_backed
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+    }
+    Ok(())
+}
+
+/// SHA-256 of the campaign's serialized account data
+fn campaign_state_hash(campaign: &Campaign) -> Result<[u8; 32]> {
+    let mut data = Vec::with_capacity(Campaign::INIT_SPACE);
+    campaign.serialize(&mut data)?;
+    Ok(hashv(&[&data]).to_bytes())
+}
+
+/// Reads a program account that may not have been created yet
+fn load_optional<T: AccountDeserialize>(info: &AccountInfo) -> Result<Option<T>> {
+    if info.owner != &crate::ID || info.data_is_empty() {
+        return Ok(None);
+    }
+    let data = info.try_borrow_data()?;
+    Ok(Some(T::try_deserialize(&mut &data[..])?))
+}
+
+/// When the protocol enforces the whitelist, the beneficiary needs an
+/// allowed registry entry. Without a protocol config any beneficiary is fine.
+fn require_beneficiary_allowed(
+    protocol_config: &AccountInfo,
+    registry: &AccountInfo,
+) -> Result<()> {
+    let Some(config) = load_optional::<ProtocolConfig>(protocol_config)? else {
+        return Ok(());
+    };
+    if !config.enforce_beneficiary_whitelist {
+        return Ok(());
+    }
+
+    let allowed = load_optional::<BeneficiaryRegistry>(registry)?
+        .is_some_and(|entry| entry.allowed);
+    require!(allowed, ErrorCode::BeneficiaryNotAllowed);
+    Ok(())
+}
+
+/// Spam guard: a protocol-wide cap on open campaigns per creator (0 for none)
+fn require_below_active_cap(protocol_config: &AccountInfo, active: u32) -> Result<()> {
+    let Some(config) = load_optional::<ProtocolConfig>(protocol_config)? else {
+        return Ok(());
+    };
+    require!(
+        config.max_active_per_creator == 0 || active < config.max_active_per_creator,
+        ErrorCode::TooManyActiveCampaigns
+    );
+    Ok(())
+}
+
+/// Treasury's cut of the vault remainder on finalize. Without a protocol config
+/// or with a zero split everything stays with the authority.
+fn treasury_share(
+    protocol_config: &AccountInfo,
+    treasury: &AccountInfo,
+    remainder: u64,
+) -> Result<u64> {
+    let Some(config) = load_optional::<ProtocolConfig>(protocol_config)? else {
+        return Ok(0);
+    };
+    if config.rent_split_bps == 0 {
+        return Ok(0);
+    }
+    require_keys_eq!(treasury.key(), config.treasury, ErrorCode::InvalidTreasury);
+    bps_of(remainder, config.rent_split_bps)
+}
+
+/// Accounts needed to pay a campaign out and close its vault
+struct Settlement<'a, 'info> {
+    campaign: &'a mut Account<'info, Campaign>,
+    vault_lamports: &'a UncheckedAccount<'info>,
+    vault_bump: u8,
+    authority: AccountInfo<'info>,
+    global_stats: &'a mut Account<'info, GlobalStats>,
+    creator_profile: &'a mut Account<'info, CreatorProfile>,
+    system_program: AccountInfo<'info>,
+    protocol_config: AccountInfo<'info>,
+    treasury: AccountInfo<'info>,
+    /// Takes the rent excess instead of the authority on a `cranker_reward` crank
+    cranker: Option<AccountInfo<'info>>,
+    /// The caller creates the reward mint (`finalize_and_reward`)
+    creates_reward_mint: bool,
+}
+
+/// Pays exactly the accounted funds (less the fee) to `recipient`, sweeps the vault
+/// remainder (rent, unused match pool) to the authority and marks the campaign
+/// finalized. Returns the lamports paid to `recipient`.
+fn settle<'info>(settlement: Settlement<'_, 'info>, recipient: AccountInfo<'info>) -> Result<u64> {
+    let Settlement {
+        campaign,
+        vault_lamports,
+        vault_bump,
+        authority,
+        global_stats,
+        creator_profile,
+        system_program,
+        protocol_config,
+        treasury,
+        cranker,
+        creates_reward_mint,
+    } = settlement;
+
+    // Without the mint claim_reward could never pay backers
+    require!(
+        campaign.reward_rate == 0 || creates_reward_mint,
+        ErrorCode::RewardsRequireMint
+    );
+
+    require!(!campaign.is_failed, ErrorCode::CampaignFailed);
+    require!(!campaign.dispute_active, ErrorCode::DisputeActive);
+    require!(
+        !campaign.overflow_in_progress,
+        ErrorCode::OverflowDistributionInProgress
+    );
+    let amount = campaign.funds;
+    require!(amount > 0, ErrorCode::NothingToFinalize);
+
+    // Strict campaigns are all-or-nothing at exactly the target
+    if campaign.strict_target {
+        require!(raised(campaign)? == campaign.target, ErrorCode::TargetNotReached);
+    }
+
+    let (dust, fee, payout) = payout_split(campaign)?;
+    // A payout also needs enough separate deposits; a dust sweep does not
+    require!(
+        dust || campaign.deposit_count >= campaign.min_deposit_count,
+        ErrorCode::NotEnoughDeposits
+    );
+
+    let campaign_key = campaign.key();
+    let vault = vault_lamports.to_account_info();
+    if payout > 0 {
+        transfer_from_vault(&system_program, &vault, &campaign_key, vault_bump, &recipient, payout)?;
+    }
+    if fee > 0 {
+        transfer_from_vault(&system_program, &vault, &campaign_key, vault_bump, &authority, fee)?;
+    }
+
+    // Rent and stray lamports left in the vault, split with the protocol treasury
+    // when configured. The unused match pool and swept dust are not the
+    // protocol's to share.
+    let remaining = vault.lamports();
+    let swept = if dust { amount } else { 0 };
+    let splittable = remaining
+        .saturating_sub(campaign.match_pool)
+        .saturating_sub(swept);
+    let treasury_share = treasury_share(&protocol_config, &treasury, splittable)?;
+    if treasury_share > 0 {
+        transfer_from_vault(&system_program, &vault, &campaign_key, vault_bump, &treasury, treasury_share)?;
+    }
+    // Accounted funds are already paid out, so a cranker can only get rent
+    // excess; the unused match pool still goes back to the authority. A dust
+    // sweep leaves the accounted funds in the vault, so it pays no tip.
+    let rest = remaining - treasury_share;
+    let tip = if cranker.is_some() && !dust {
+        rest.saturating_sub(campaign.match_pool)
+    } else {
+        0
+    };
+    if let Some(cranker) = cranker.filter(|_| tip > 0) {
+        transfer_from_vault(&system_program, &vault, &campaign_key, vault_bump, &cranker, tip)?;
+        emit!(CrankerTipped {
+            campaign: campaign_key,
+            cranker: cranker.key(),
+            amount: tip,
+        });
+    }
+    if rest > tip {
+        transfer_from_vault(
+            &system_program,
+            &vault,
+            &campaign_key,
+            vault_bump,
+            &authority,
+            rest - tip,
+        )?;
+    }
+
+    // Mark campaign finalized and zero out accounted funds
+    let total_raised = raised(campaign)?;
+    let now = Clock::get()?.unix_timestamp;
+    campaign.is_finalized = true;
+    campaign.final_funds = amount;
+    campaign.finalized_at = now;
+    campaign.funds = 0;
+    campaign.match_pool = 0;
+
+    if dust {
+        emit!(DustSwept {
+            campaign: campaign_key,
+            authority: authority.key(),
+            amount,
+            min_finalize_amount: campaign.min_finalize_amount,
+        });
+    } else {
+        emit!(CampaignCompleted {
+            campaign: campaign_key,
+            total_raised,
+            target: campaign.target,
+            donor_count: campaign.donor_count,
+            beneficiary: recipient.key(),
+            fee_paid: fee,
+            duration_seconds: now.saturating_sub(campaign.created_at),
+        });
+    }
+
+    global_stats.active_campaigns = global_stats
+        .active_campaigns
+        .checked_sub(1)
+        .ok_or(ErrorCode::MathOverflow)?;
+    global_stats.finalized_campaigns = global_stats
+        .finalized_campaigns
+        .checked_add(1)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    // A dust sweep pays nothing out, so it counts against the creator and adds
+    // nothing to what they raised
+    let credited = if dust { 0 } else { total_raised };
+    record_outcome(creator_profile, !dust, credited, campaign.total_refunded)?;
+
+    Ok(payout)
+}
+
+/// How `settle` divides the accounted funds: (dust sweep, fee, payout).
+/// Below min_finalize_amount the campaign is treated as abandoned dust and
+/// everything goes to the authority. Otherwise the fee is taken out of the
+/// payout and goes to the authority.
+fn payout_split(campaign: &Campaign) -> Result<(bool, u64, u64)> {
+    let amount = campaign.funds;
+    let dust = amount < campaign.min_finalize_amount;
+    if dust {
+        return Ok((true, 0, 0));
+    }
+    let fee = bps_of(amount, campaign.fee_bps)?;
+    Ok((false, fee, amount - fee))
+}
+
+/// Closes a program-owned account by hand, moving its lamports to `destination`
+fn close_account<'info>(info: &AccountInfo<'info>, destination: &AccountInfo<'info>) -> Result<()> {
+    let lamports = info.lamports();
+    info.sub_lamports(lamports)?;
+    destination.add_lamports(lamports)?;
+    info.assign(&system_program::ID);
+    info.resize(0)?;
+    Ok(())
+}
+
+/// System transfer out of the campaign vault, signed with the vault PDA seeds
+fn transfer_from_vault<'info>(
+    system_program: &AccountInfo<'info>,
+    vault_lamports: &AccountInfo<'info>,
+    campaign: &Pubkey,
+    vault_bump: u8,
+    to: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let vault_lamports_seeds: &[&[u8]] = &[b"vault_lamports", campaign.as_ref(), &[vault_bump]];
+
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Transfer {
+                from: vault_lamports.clone(),
+                to: to.clone(),
+            },
+            &[vault_lamports_seeds],
+        ),
+        amount,
+    )
+}
+
+/// System transfer out of the creator bond, signed with the bond PDA seeds
+fn transfer_from_bond<'info>(
+    system_program: &AccountInfo<'info>,
+    creator_bond: &AccountInfo<'info>,
+    campaign: &Pubkey,
+    bond_bump: u8,
+    to: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let creator_bond_seeds: &[&[u8]] = &[b"creator_bond", campaign.as_ref(), &[bond_bump]];
+
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Transfer {
+                from: creator_bond.clone(),
+                to: to.clone(),
+            },
+            &[creator_bond_seeds],
+        ),
+        amount,
+    )
+}
+
+/// Hands a posted bond to the donors, pro-rata to what they still have in
+/// the campaign; `slash_bond` pays the shares out
+fn forfeit_bond(campaign: &mut Campaign) -> Result<()> {
+    let matched = if campaign.match_reclaimed {
+        0
+    } else {
+        campaign.total_matched
+    };
+    campaign.bond_state = BOND_FORFEITED;
+    campaign.bond_basis = raised(campaign)?.saturating_sub(matched).max(1);
+    campaign.bond_distributed = 0;
+    campaign.bond_processed = 0;
+    Ok(())
+}
+
+/// The settings `initialize` stored, so imported campaigns can be validated
+/// the same way
+fn campaign_config(campaign: &Campaign) -> CampaignConfig {
+    CampaignConfig {
+        match_tiers: campaign.match_tiers,
+        strict_target: campaign.strict_target,
+        refund_policy: campaign.refund_policy,
+        large_deposit_threshold: campaign.large_deposit_threshold,
+        deadline: campaign.deadline,
+        backup_beneficiary: campaign.backup_beneficiary,
+        backup_delay: campaign.backup_delay,
+        allowed_amounts: campaign.allowed_amounts,
+        fee_bps: campaign.fee_bps,
+        block_beneficiary_deposits: campaign.block_beneficiary_deposits,
+        team: campaign.team,
+        end_action: campaign.end_action,
+        verbose_logs: campaign.verbose_logs,
+        allow_partial_withdrawals: campaign.allow_partial_withdrawals,
+        withdraw_floor: campaign.withdraw_floor,
+        withdraw_reserve: campaign.withdraw_reserve,
+        min_finalize_amount: campaign.min_finalize_amount,
+        max_per_donor: campaign.max_per_donor,
+        reward_rate: campaign.reward_rate,
+        overshoot_tolerance_bps: campaign.overshoot_tolerance_bps,
+        round_up_to_tier: campaign.round_up_to_tier,
+        max_total_withdrawals: campaign.max_total_withdrawals,
+        gated_content_hash: campaign.gated_content_hash,
+        unlock_threshold: campaign.unlock_threshold,
+        stream_payout: campaign.stream_payout,
+        stream_bps: campaign.stream_bps,
+        min_deposit_count: campaign.min_deposit_count,
+        arbiter: campaign.arbiter,
+        dust_refund_threshold: campaign.dust_refund_threshold,
+        require_social: campaign.require_social,
+        social_issuer: campaign.social_issuer,
+        bond_amount: campaign.bond_amount,
+        min_slot_gap: campaign.min_slot_gap,
+        cranker_reward: campaign.cranker_reward,
+    }
+}
+
+/// Init-time validation of the campaign settings
+fn validate_campaign_config(target: u64, config: &CampaignConfig, now: i64) -> Result<()> {
+    require!(target > 0, ErrorCode::InvalidAmount);
+    require!(
+        config.deadline == 0 || config.deadline > now,
+        ErrorCode::InvalidDeadline
+    );
+    if config.backup_beneficiary != Pubkey::default() {
+        require!(
+            config.deadline > 0 && config.backup_delay >= 0,
+            ErrorCode::InvalidBackupBeneficiary
+        );
+    }
+    require!(config.fee_bps as u64 <= BPS_DENOMINATOR, ErrorCode::InvalidFee);
+    validate_refund_policy(&config.refund_policy)?;
+    require!(
+        config.end_action <= END_ACTION_ALLOW_FINALIZE_ONLY,
+        ErrorCode::InvalidEndAction
+    );
+    require!(
+        config.withdraw_floor <= target
+            && config.withdraw_reserve <= target
+            && config.max_total_withdrawals <= target,
+        ErrorCode::InvalidWithdrawConfig
+    );
+    require!(
+        config.min_finalize_amount <= target,
+        ErrorCode::InvalidMinFinalizeAmount
+    );
+    require!(
+        config.dust_refund_threshold <= target,
+        ErrorCode::InvalidDustRefundThreshold
+    );
+    require!(
+        !config.require_social || config.social_issuer != Pubkey::default(),
+        ErrorCode::InvalidSocialConfig
+    );
+    require!(
+        !config.round_up_to_tier || config.allowed_amounts.iter().any(|&tier| tier > 0),
+        ErrorCode::RoundUpWithoutTiers
+    );
+    // A team payout has to vest, so it cannot stream
+    require!(
+        !config.stream_payout
+            || (config.stream_bps > 0
+                && config.stream_bps as u64 <= BPS_DENOMINATOR
+                && config.team.iter().all(|share| share.share_bps == 0)),
+        ErrorCode::InvalidStreamConfig
+    );
+    // Above-threshold deposits are escrowed for confirm_large_deposit, which
+    // streaming and social-gated campaigns refuse, so they would have nowhere to go
+    require!(
+        !(config.stream_payout || config.require_social) || config.large_deposit_threshold == 0,
+        ErrorCode::LargeDepositsUnconfirmable
+    );
+    // Strict campaigns finalize at exactly the target, so they cannot overshoot it
+    require!(
+        config.overshoot_tolerance_bps as u64 <= BPS_DENOMINATOR
+            && (config.overshoot_tolerance_bps == 0 || !config.strict_target),
+        ErrorCode::InvalidOvershootTolerance
+    );
+    validate_team(&config.team)?;
+    validate_match_tiers(&config.match_tiers)
+}
+
+/// Penalty within 10_000 bps, non-negative windows, and a refund window that
+/// outlasts the lockup so refunds stay possible
+fn validate_refund_policy(policy: &RefundPolicy) -> Result<()> {
+    require!(
+        policy.refund_penalty_bps as u64 <= BPS_DENOMINATOR,
+        ErrorCode::InvalidFee
+    );
+    require!(
+        policy.refund_window >= 0
+            && policy.lockup_seconds >= 0
+            && (policy.refund_window == 0 || policy.refund_window > policy.lockup_seconds),
+        ErrorCode::InvalidRefundPolicy
+    );
+    Ok(())
+}
+
+/// An empty team is allowed; otherwise every slot with a share names a
+/// distinct member and the shares add up to exactly 10_000 bps
+fn validate_team(team: &[TeamShare; TEAM_SIZE]) -> Result<()> {
+    let mut total_bps: u64 = 0;
+    for (i, share) in team.iter().enumerate() {
+        if share.share_bps == 0 {
+            continue;
+        }
+        require!(
+            share.member != Pubkey::default()
+                && !team[..i]
+                    .iter()
+                    .any(|other| other.share_bps > 0 && other.member == share.member),
+            ErrorCode::InvalidTeamShares
+        );
+        total_bps += u64::from(share.share_bps);
+    }
+    require!(
+        total_bps == 0 || total_bps == BPS_DENOMINATOR,
+        ErrorCode::InvalidTeamShares
+    );
+    Ok(())
+}
+
+/// Lamports of the vested total allocated to `member`. The last member with a
+/// share receives the rounding dust so the whole total is claimable.
+fn team_allocation(vesting: &TeamVesting, member: Pubkey) -> Result<u64> {
+    let last = vesting
+        .team
+        .iter()
+        .rposition(|share| share.share_bps > 0)
+        .ok_or(ErrorCode::NoTeam)?;
+    let index = vesting
+        .team
+        .iter()
+        .position(|share| share.share_bps > 0 && share.member == member)
+        .ok_or(ErrorCode::NotTeamMember)?;
+
+    if index == last {
+        let mut others: u64 = 0;
+        for share in vesting.team[..last].iter() {
+            others = others
+                .checked_add(bps_of(vesting.total, share.share_bps)?)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+        return vesting
+            .total
+            .checked_sub(others)
+            .ok_or(error!(ErrorCode::MathOverflow));
+    }
+
+    bps_of(vesting.total, vesting.team[index].share_bps)
+}
+
+/// How much more a donor who has put in `contributed` may add under the
+/// per-donor cap (`u64::MAX` when the campaign has no cap)
+fn donor_cap_left(campaign: &Campaign, contributed: u64) -> u64 {
+    if campaign.max_per_donor == 0 {
+        u64::MAX
+    } else {
+        campaign.max_per_donor.saturating_sub(contributed)
+    }
+}
+
+/// Most a campaign accepts: the target plus its overshoot tolerance
+fn deposit_ceiling(campaign: &Campaign) -> Result<u64> {
+    campaign
+        .target
+        .checked_add(bps_of(campaign.target, campaign.overshoot_tolerance_bps)?)
+        .ok_or(error!(ErrorCode::MathOverflow))
+}
+
+/// Everything raised towards the target: funds still held plus partial
+/// withdrawals and streamed payouts
+fn raised(campaign: &Campaign) -> Result<u64> {
+    campaign
+        .funds
+        .checked_add(campaign.total_withdrawn)
+        .and_then(|total| total.checked_add(campaign.total_streamed))
+        .ok_or(error!(ErrorCode::MathOverflow))
+}
+
+/// Flags the campaign failed, which opens fee-free refunds and the match
+/// claw-back, and books it on the creator's profile and the global counters
+fn fail_campaign(
+    campaign: &mut Campaign,
+    creator_profile: &mut CreatorProfile,
+    stats: &mut GlobalStats,
+) -> Result<()> {
+    campaign.is_failed = true;
+
+    // Everything still in the vault is now owed back to the donors
+    record_outcome(creator_profile, false, 0, campaign.funds)?;
+
+    stats.active_campaigns = stats
+        .active_campaigns
+        .checked_sub(1)
+        .ok_or(ErrorCode::MathOverflow)?;
+    stats.failed_campaigns = stats
+        .failed_campaigns
+        .checked_add(1)
+        .ok_or(ErrorCode::MathOverflow)?;
+    Ok(())
+}
+
+/// Books a settled campaign on its creator's profile
+fn record_outcome(
+    profile: &mut CreatorProfile,
+    succeeded: bool,
+    raised: u64,
+    refunded: u64,
+) -> Result<()> {
+    profile.active_campaigns = profile
+        .active_campaigns
+        .checked_sub(1)
+        .ok_or(ErrorCode::MathOverflow)?;
+    if succeeded {
+        profile.campaigns_succeeded = profile
+            .campaigns_succeeded
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+    } else {
+        profile.campaigns_failed = profile
+            .campaigns_failed
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+    }
+    profile.total_raised = profile
+        .total_raised
+        .checked_add(raised)
+        .ok_or(ErrorCode::MathOverflow)?;
+    profile.total_refunded = profile
+        .total_refunded
+        .checked_add(refunded)
+        .ok_or(ErrorCode::MathOverflow)?;
+    Ok(())
+}
+
+/// Checks that the instruction right before the current one is an ed25519
+/// program verification of `message` under `signer`. Signature, key and message
+/// must all live inside that instruction so none of them can be swapped out.
+fn verify_ed25519_signature(
+    instructions: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let current = load_current_index_checked(instructions)?;
+    require!(current > 0, ErrorCode::InvalidVoucher);
+    let verify = load_instruction_at_checked(usize::from(current - 1), instructions)?;
+    require_keys_eq!(verify.program_id, ed25519_program::ID, ErrorCode::InvalidVoucher);
+
+    // [signature count, padding], then one record of seven u16 offsets
+    let data = &verify.data;
+    require!(data.len() >= 16 && data[0] == 1, ErrorCode::InvalidVoucher);
+    let offset = |i: usize| usize::from(u16::from_le_bytes([data[2 + 2 * i], data[3 + 2 * i]]));
+    let (signature_ix, key_at, key_ix) = (offset(1), offset(2), offset(3));
+    let (message_at, message_len, message_ix) = (offset(4), offset(5), offset(6));
+    let this_ix = usize::from(u16::MAX);
+    require!(
+        signature_ix == this_ix && key_ix == this_ix && message_ix == this_ix,
+        ErrorCode::InvalidVoucher
+    );
+
+    let key = data.get(key_at..key_at + 32).ok_or(ErrorCode::InvalidVoucher)?;
+    let signed = data
+        .get(message_at..message_at + message_len)
+        .ok_or(ErrorCode::InvalidVoucher)?;
+    require!(key == signer.as_ref() && signed == message, ErrorCode::InvalidVoucher);
+    Ok(())
+}
+
+/// `part` as a percentage of `whole`, rounded down and capped at 100
+fn percent(part: u64, whole: u64) -> u64 {
+    if whole == 0 {
+        return 100;
+    }
+    (u128::from(part) * 100 / u128::from(whole)).min(100) as u64
+}
+
+/// `amount * bps / 10_000`, rounded down
+fn bps_of(amount: u64, bps: u16) -> Result<u64> {
+    let value = amount as u128 * bps as u128 / BPS_DENOMINATOR as u128;
+    u64::try_from(value).map_err(|_| error!(ErrorCode::MathOverflow))
+}
+
+/// Tiers are consumed in order, so a zero-sized tier ends the curve
+fn validate_match_tiers(tiers: &[MatchTier; MATCH_TIER_COUNT]) -> Result<()> {
+    let mut ended = false;
+    for tier in tiers.iter() {
+        if tier.amount == 0 {
+            ended = true;
+        } else {
+            require!(!ended, ErrorCode::InvalidMatchTiers);
+        }
+    }
+    Ok(())
+}
+
+/// Match owed for the slice `[before, after)` of a donor's cumulative contribution.
+/// Tier `i` covers the `tiers[i].amount` lamports following the previous tiers.
+fn match_for_range(
+    tiers: &[MatchTier; MATCH_TIER_COUNT],
+    before: u64,
+    after: u64,
+) -> Result<u64> {
+    let mut matched: u128 = 0;
+    let mut tier_start: u128 = 0;
+    for tier in tiers.iter().take_while(|tier| tier.amount > 0) {
+        let tier_end = tier_start + tier.amount as u128;
+        let lo = (before as u128).max(tier_start);
+        let hi = (after as u128).min(tier_end);
+        if hi > lo {
+            matched += (hi - lo) * tier.rate_bps as u128 / BPS_DENOMINATOR as u128;
+        }
+        tier_start = tier_end;
+    }
+    u64::try_from(matched).map_err(|_| error!(ErrorCode::MathOverflow))
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct MatchTier {
+    /// Lamports of a donor's cumulative contribution covered by this tier
+    pub amount: u64,
+    /// Match rate in basis points (10_000 matches 1:1)
+    pub rate_bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct TeamShare {
+    pub member: Pubkey,
+    /// Member's split of the payout in basis points (0 marks an unused slot)
+    pub share_bps: u16,
+}
+
+/// Voluntary refund rules. Windows count from the donor's first contribution.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct RefundPolicy {
+    /// Let donors take their contribution back before finalize
+    pub refunds_enabled: bool,
+    /// Share of a refund kept in the campaign for the beneficiary
+    pub refund_penalty_bps: u16,
+    /// Seconds after which refunds close (0 for no window)
+    pub refund_window: i64,
+    /// Seconds before refunds open
+    pub lockup_seconds: i64,
+    /// Most gross lamports the campaign refunds in total (0 for no cap)
+    pub max_total_refunds: u64,
+    /// update_refund_policy is rejected once set
+    pub immutable: bool,
+}
+
+/// Optional campaign settings chosen at initialization
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct CampaignConfig {
+    pub match_tiers: [MatchTier; MATCH_TIER_COUNT],
+    /// Finalize only when `funds == target`
+    pub strict_target: bool,
+    /// Rules for donors taking their contribution back before finalize
+    pub refund_policy: RefundPolicy,
+    /// Deposits above this need a separate confirmation (0 disables)
+    pub large_deposit_threshold: u64,
+    /// Unix timestamp after which deposits stop (0 for no deadline)
+    pub deadline: i64,
+    /// May finalize in place of the beneficiary (default key for none)
+    pub backup_beneficiary: Pubkey,
+    /// Seconds after the deadline before the backup may finalize
+    pub backup_delay: i64,
+    /// Exact deposit amounts accepted; zero slots are unused, all zero allows any amount
+    pub allowed_amounts: [u64; 4],
+    /// Share of the payout kept by the authority at finalize
+    pub fee_bps: u16,
+    /// Reject deposits made by the beneficiary itself
+    pub block_beneficiary_deposits: bool,
+    /// Members splitting a vested payout (all empty for a single beneficiary)
+    pub team: [TeamShare; TEAM_SIZE],
+    /// One of the `END_ACTION_*` values, applied by `process_deadline`
+    pub end_action: u8,
+    /// Emit a plain `DEPOSIT|...` log line per deposit for log scrapers
+    pub verbose_logs: bool,
+    /// Beneficiary may `withdraw` before finalize
+    pub allow_partial_withdrawals: bool,
+    /// Funds required before the first withdrawal (0 disables)
+    pub withdraw_floor: u64,
+    /// Funds that must stay in the campaign after a withdrawal
+    pub withdraw_reserve: u64,
+    /// Below this, finalize sweeps the funds to the authority as dust (0 disables)
+    pub min_finalize_amount: u64,
+    /// Most a single donor may contribute in total (0 for no cap)
+    pub max_per_donor: u64,
+    /// Reward token base units minted per contributed lamport (0 disables rewards)
+    pub reward_rate: u64,
+    /// Deposits may overshoot the target by up to this share of it before clamping
+    pub overshoot_tolerance_bps: u16,
+    /// Round deposits below a tier up to the next tier instead of requiring exact tiers
+    pub round_up_to_tier: bool,
+    /// Most the beneficiary may withdraw before finalize (0 for no cap)
+    pub max_total_withdrawals: u64,
+    /// Commitment to backer-only content, released by `unlock_content`
+    pub gated_content_hash: [u8; 32],
+    /// Contribution needed to unlock the gated content
+    pub unlock_threshold: u64,
+    /// Forward `stream_bps` of every deposit to the beneficiary right away
+    pub stream_payout: bool,
+    pub stream_bps: u16,
+    /// Successful deposits (not donors) required before a payout finalize
+    pub min_deposit_count: u32,
+    /// Resolves donor disputes (default key disables disputes)
+    pub arbiter: Pubkey,
+    /// Overflow shares below this go to the beneficiary instead of the donor (0 disables)
+    pub dust_refund_threshold: u64,
+    /// Deposits need a `SocialAttestation` from `social_issuer`
+    pub require_social: bool,
+    pub social_issuer: Pubkey,
+    /// Lamports the creator escrows at initialize (0 for no bond)
+    pub bond_amount: u64,
+    /// Slots a donor must wait between deposits (0 disables)
+    pub min_slot_gap: u64,
+    /// `crank_finalize` pays the vault's rent excess to the cranker
+    pub cranker_reward: bool,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Campaign{
+   pub funds: u64,
+   pub target: u64,
+   pub campaign_id: u64,
+   pub authority: Pubkey,
+   pub beneficiary: Pubkey,
+   pub is_finalized: bool,
+   pub match_tiers: [MatchTier; MATCH_TIER_COUNT],
+   /// Lamports in the vault reserved for matching, not yet counted in `funds`
+   pub match_pool: u64,
+   /// Donors with a non-zero contribution
+   pub donor_count: u32,
+   pub created_at: i64,
+   pub finalized_at: i64,
+   /// Accounted funds paid out by finalize
+   pub final_funds: u64,
+   pub strict_target: bool,
+   pub refund_policy: RefundPolicy,
+   pub is_cancelled: bool,
+   pub large_deposit_threshold: u64,
+   /// 0 when the campaign has no deadline
+   pub deadline: i64,
+   pub backup_beneficiary: Pubkey,
+   pub backup_delay: i64,
+   /// Overflow snapshot taken when a distribution starts
+   pub overflow_total: u64,
+   pub overflow_basis: u64,
+   pub overflow_distributed: u64,
+   /// Contributions paid so far in the current distribution
+   pub overflow_processed: u32,
+   pub overflow_in_progress: bool,
+   /// Distributions started so far; the current one while in progress
+   pub overflow_round: u32,
+   /// Fixed deposit tiers (all zero for free-form deposits)
+   pub allowed_amounts: [u64; 4],
+   pub fee_bps: u16,
+   pub block_beneficiary_deposits: bool,
+   pub team: [TeamShare; TEAM_SIZE],
+   /// Set by `mark_failed` once the deadline passed short of the target
+   pub is_failed: bool,
+   /// Match credited to donors and still held in funds
+   pub total_matched: u64,
+   pub match_reclaimed: bool,
+   pub end_action: u8,
+   /// `process_deadline` has run
+   pub deadline_processed: bool,
+   pub verbose_logs: bool,
+   pub allow_partial_withdrawals: bool,
+   pub withdraw_floor: u64,
+   pub withdraw_reserve: u64,
+   /// Paid out early through `withdraw`; still counts towards the target
+   pub total_withdrawn: u64,
+   pub min_finalize_amount: u64,
+   pub max_per_donor: u64,
+   pub reward_rate: u64,
+   /// Set once `finalize_and_reward` has created the reward mint
+   pub rewards_ready: bool,
+   pub reward_mint_bump: u8,
+   pub overshoot_tolerance_bps: u16,
+   pub round_up_to_tier: bool,
+   pub max_total_withdrawals: u64,
+   /// Gross contributions refunded so far
+   pub total_refunded: u64,
+   pub gated_content_hash: [u8; 32],
+   pub unlock_threshold: u64,
+   /// Head of the comment hash chain (zero before the first comment)
+   pub last_comment_hash: [u8; 32],
+   pub comment_count: u64,
+   pub stream_payout: bool,
+   pub stream_bps: u16,
+   /// Lamports already forwarded to the beneficiary by streaming deposits
+   pub total_streamed: u64,
+   pub min_deposit_count: u32,
+   /// Successful deposits across all deposit paths
+   pub deposit_count: u32,
+   pub arbiter: Pubkey,
+   /// Payouts and withdrawals are frozen while set
+   pub dispute_active: bool,
+   pub dispute_count: u32,
+   pub dust_refund_threshold: u64,
+   /// Overflow shares redirected to the beneficiary as dust
+   pub overflow_dust_redirected: u64,
+   /// Set by `process_deadline` under END_ACTION_PAUSE, cleared by `extend_deadline`
+   pub is_paused: bool,
+   pub require_social: bool,
+   /// Trusted attestation issuer for social-gated campaigns
+   pub social_issuer: Pubkey,
+   /// Contribution accounts created for this campaign
+   pub contribution_accounts: u32,
+   /// Contribution accounts closed by `finalize_and_cleanup`
+   pub children_closed: u32,
+   /// Every contribution account has been closed
+   pub fully_cleaned: bool,
+   pub bond_amount: u64,
+   pub bond_state: u8,
+   /// Outstanding contributions snapshotted when the bond is forfeited
+   pub bond_basis: u64,
+   pub bond_distributed: u64,
+   /// Contributions paid their bond share so far
+   pub bond_processed: u32,
+   pub min_slot_gap: u64,
+   pub cranker_reward: bool,
+   /// Sum of the named contributions' amounts, the basis for overflow shares
+   pub contributed: u64,
+   /// `update_target` left funds above the new ceiling for `distribute_overflow`
+   pub overflow_pending: bool,
+}
+
+/// Protocol-level settings managed by the admin
+#[account]
+#[derive(InitSpace)]
+pub struct ProtocolConfig {
+    pub admin: Pubkey,
+    /// Only registered beneficiaries may receive payouts
+    pub enforce_beneficiary_whitelist: bool,
+    pub bump: u8,
+    /// Receives rent_split_bps of the vault remainder on finalize
+    pub treasury: Pubkey,
+    pub rent_split_bps: u16,
+    /// Open campaigns allowed per creator (0 for no cap)
+    pub max_active_per_creator: u32,
+}
+
+/// Admin-vetted beneficiary entry, one per beneficiary
+#[account]
+#[derive(InitSpace)]
+pub struct BeneficiaryRegistry {
+    pub beneficiary: Pubkey,
+    pub allowed: bool,
+    pub bump: u8,
+}
+
+/// Platform-wide campaign counters, kept in step with every state transition
+#[account]
+#[derive(InitSpace)]
+pub struct GlobalStats {
+    pub total_campaigns: u64,
+    /// Initialized and neither finalized, cancelled nor failed
+    pub active_campaigns: u64,
+    pub finalized_campaigns: u64,
+    /// Cancelled, or failed through `mark_failed` or a dispute resolved for
+    /// the donors
+    pub failed_campaigns: u64,
+    pub bump: u8,
+}
+
+/// Track record of one campaign creator, across all of their campaigns
+#[account]
+#[derive(InitSpace)]
+pub struct CreatorProfile {
+    pub creator: Pubkey,
+    pub campaigns_created: u32,
+    /// Created and not yet finalized, cancelled or failed
+    pub active_campaigns: u32,
+    /// Finalized with a payout
+    pub campaigns_succeeded: u32,
+    /// Cancelled, marked failed, or finalized as a dust sweep
+    pub campaigns_failed: u32,
+    /// Raised by the succeeded campaigns
+    pub total_raised: u64,
+    /// Refunded before a success, or left owed to donors by a failure
+    pub total_refunded: u64,
+    pub bump: u8,
+}
+
+/// Giving record of one donor, across every campaign they backed
+#[account]
+#[derive(InitSpace)]
+pub struct DonorProfile {
+    pub donor: Pubkey,
+    /// Counted into the donor's named contributions, by any deposit path or
+    /// a reveal
+    pub total_contributed: u64,
+    /// Campaigns where the donor's contribution was opened
+    pub campaigns_backed: u32,
+    /// Gross lamports taken back through `refund`
+    pub total_refunded: u64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct GlobalStatsView {
+    pub total_campaigns: u64,
+    pub active_campaigns: u64,
+    pub finalized_campaigns: u64,
+    pub failed_campaigns: u64,
+}
+
+/// Compact record kept after a finalized campaign account is closed
+#[account]
+#[derive(InitSpace)]
+pub struct ArchivedCampaign {
+    pub campaign: Pubkey,
+    pub authority: Pubkey,
+    pub campaign_id: u64,
+    pub final_funds: u64,
+    pub donor_count: u32,
+    pub created_at: i64,
+    pub finalized_at: i64,
+    /// SHA-256 of the campaign account data at archival
+    pub state_hash: [u8; 32],
+    pub bump: u8,
+}
+
+/// Lamports a donor escrowed for a relayer to deposit on their behalf
+#[account]
+#[derive(InitSpace)]
+pub struct DepositAuthorization {
+    pub campaign: Pubkey,
+    pub donor: Pubkey,
+    pub relayer: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
+}
+
+/// Issuer's claim that `subject` controls a linked social account
+#[account]
+#[derive(InitSpace)]
+pub struct SocialAttestation {
+    pub issuer: Pubkey,
+    pub subject: Pubkey,
+    /// Unix timestamp the attestation stops counting at (0 for never)
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+/// Donor-funded pool that relayed vouchers draw deposits from
+#[account]
+#[derive(InitSpace)]
+pub struct VoucherEscrow {
+    pub donor: Pubkey,
+    pub bump: u8,
+}
+
+/// Marks a voucher nonce as spent
+#[account]
+#[derive(InitSpace)]
+pub struct VoucherNonce {
+    pub campaign: Pubkey,
+    pub donor: Pubkey,
+    pub nonce: u64,
+    pub bump: u8,
+}
+
+/// Escrowed large deposit awaiting the donor's confirmation
+#[account]
+#[derive(InitSpace)]
+pub struct PendingDeposit {
+    pub campaign: Pubkey,
+    pub donor: Pubkey,
+    pub amount: u64,
+    pub requested_slot: u64,
+    pub bump: u8,
+}
+
+#[event]
+pub struct Refunded {
+    pub campaign: Pubkey,
+    pub donor: Pubkey,
+    /// Contribution given back
+    pub gross: u64,
+    /// Kept in the campaign for the beneficiary
+    pub fee: u64,
+    /// Lamports returned to the donor
+    pub net: u64,
+}
+
+/// Single completion record for indexers, emitted when a campaign pays out
+#[event]
+pub struct CampaignCompleted {
+    pub campaign: Pubkey,
+    pub total_raised: u64,
+    pub target: u64,
+    pub donor_count: u32,
+    /// Account that received the payout
+    pub beneficiary: Pubkey,
+    pub fee_paid: u64,
+    pub duration_seconds: i64,
+}
+
+/// Finalize found only dust and swept it to the authority instead of paying out
+#[event]
+pub struct DustSwept {
+    pub campaign: Pubkey,
+    pub authority: Pubkey,
+    /// Accounted funds swept along with the vault remainder
+    pub amount: u64,
+    pub min_finalize_amount: u64,
+}
+
+/// Part of a deposit was turned away because it would overshoot the target
+#[event]
+pub struct DepositClamped {
+    pub campaign: Pubkey,
+    pub donor: Pubkey,
+    pub requested: u64,
+    pub accepted: u64,
+}
+
+/// A deposit below a tier was raised to that tier
+#[event]
+pub struct DepositRoundedUp {
+    pub campaign: Pubkey,
+    pub donor: Pubkey,
+    pub requested: u64,
+    pub rounded: u64,
+}
+
+/// One link of a campaign's comment chain; `index` counts from 1
+#[event]
+pub struct CommentPosted {
+    pub campaign: Pubkey,
+    pub donor: Pubkey,
+    pub comment_hash: [u8; 32],
+    pub chain_hash: [u8; 32],
+    pub index: u64,
+}
+
+#[event]
+pub struct DisputeRaised {
+    pub campaign: Pubkey,
+    pub donor: Pubkey,
+    pub dispute_count: u32,
+}
+
+#[event]
+pub struct DisputeResolved {
+    pub campaign: Pubkey,
+    pub arbiter: Pubkey,
+    pub favor_donors: bool,
+}
+
+#[event]
+pub struct OverflowDistributed {
+    pub campaign: Pubkey,
+    pub donor: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct CrankerTipped {
+    pub campaign: Pubkey,
+    pub cranker: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct BondShareDistributed {
+    pub campaign: Pubkey,
+    pub donor: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct OverflowDustRedirected {
+    pub campaign: Pubkey,
+    pub donor: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct EmergencyReclaimed {
+    pub campaign: Pubkey,
+    pub vault: Pubkey,
+    pub recovery: Pubkey,
+    pub admin: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct InvariantsOk {
+    pub campaign: Pubkey,
+    pub vault_balance: u64,
+    pub funds: u64,
+    pub match_pool: u64,
+    pub target: u64,
+    pub rent_reserve: u64,
+    /// Sum of the contributions supplied as receipts
+    pub receipts_total: u64,
+}
+
+#[event]
+pub struct InvariantViolation {
+    pub campaign: Pubkey,
+    /// One of the `INVARIANT_*` codes
+    pub code: u8,
+    pub expected: u64,
+    pub actual: u64,
+}
+
+/// Finalized team payout, held here and released to members over time
+#[account]
+#[derive(InitSpace)]
+pub struct TeamVesting {
+    pub campaign: Pubkey,
+    /// Lamports paid in at finalize
+    pub total: u64,
+    /// Unix timestamp the schedule starts from
+    pub start: i64,
+    /// Seconds after `start` before anything unlocks
+    pub cliff: i64,
+    /// Seconds after `start` until everything is unlocked
+    pub duration: i64,
+    pub team: [TeamShare; TEAM_SIZE],
+    pub bump: u8,
+}
+
+/// One team member's claims against a `TeamVesting`
+#[account]
+#[derive(InitSpace)]
+pub struct VestingStream {
+    pub team_vesting: Pubkey,
+    pub member: Pubkey,
+    pub claimed: u64,
+    pub bump: u8,
+}
+
+/// Deposits made under a hashed donor reference instead of a named contribution
+#[account]
+#[derive(InitSpace)]
+pub struct AnonymousDeposit {
+    pub campaign: Pubkey,
+    /// sha256(donor || salt)
+    pub commitment: [u8; 32],
+    pub amount: u64,
+    pub matched: u64,
+    pub first_deposited_at: i64,
+    pub bump: u8,
+    /// Campaign deposit number of the first deposit under this commitment
+    pub receipt_number: u64,
+    /// Slot of the latest deposit under this commitment, for `min_slot_gap`
+    pub last_deposit_slot: u64,
+}
+
+/// Per-donor record of what a donor has put into a campaign
+#[account]
+#[derive(InitSpace)]
+pub struct Contribution {
+    pub campaign: Pubkey,
+    pub donor: Pubkey,
+    /// Lamports counted from this donor
+    pub amount: u64,
+    /// Lamports matched on this donor's deposits
+    pub matched: u64,
+    pub bump: u8,
+    /// Unix timestamp of the donor's first deposit
+    pub first_contributed_at: i64,
+    /// Last overflow distribution (`Campaign::overflow_round`) that paid this
+    /// contribution, 0 for none
+    pub overflow_round: u32,
+    /// Backer reward already minted for this contribution
+    pub reward_claimed: bool,
+    /// Campaign deposit number of the donor's first deposit (1-based)
+    pub receipt_number: u64,
+    /// Already paid its share of a forfeited creator bond
+    pub bond_paid: bool,
+    /// Slot of the donor's latest deposit
+    pub last_deposit_slot: u64,
+    /// The donor has used their one dispute on this campaign
+    pub dispute_raised: bool,
+}
+
+#[derive(Accounts)]
+#[instruction(campaign_id: u64)]
+pub struct Initialize<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    /// CHECK: we only read the pubkey and store it
+    pub beneficiary: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + Campaign::INIT_SPACE,
+        seeds = [b"campaign", creator.key().as_ref(), &campaign_id.to_le_bytes()],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 0,
+        owner = system_program::ID,
+        seeds = [b"vault_lamports", campaign.key().as_ref()],
+        bump
+    )]
+
+    /// CHECK: system-owned PDA used only for lamport transfers
+    pub vault_lamports: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [b"creator_bond", campaign.key().as_ref()], bump)]
+    /// CHECK: system-owned PDA holding the creator bond, funded only when bond_amount > 0
+    pub creator_bond: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + GlobalStats::INIT_SPACE,
+        seeds = [b"global_stats"],
+        bump
+    )]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + CreatorProfile::INIT_SPACE,
+        seeds = [b"creator_profile", creator.key().as_ref()],
+        bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+
+    #[account(seeds = [b"protocol_config"], bump)]
+    /// CHECK: may be uninitialized when the protocol has no config
+    pub protocol_config: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"beneficiary_registry", beneficiary.key().as_ref()], bump)]
+    /// CHECK: may be uninitialized when the beneficiary is not registered
+    pub beneficiary_registry: UncheckedAccount<'info>,
+
+    /// Records left behind for this campaign key (anonymous deposits, nonces)
+    /// must never attach to a re-created campaign
+    #[account(
+        seeds = [b"archived_campaign", campaign.key().as_ref()],
+        bump,
+        constraint = archived_campaign.data_is_empty() @ ErrorCode::CampaignArchived,
+    )]
+    /// CHECK: only checked to be uninitialized
+    pub archived_campaign: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ValidateConfig {}
+
+#[derive(Accounts)]
+pub struct UpdateFee<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = !campaign.is_finalized @ ErrorCode::CampaignFinalized,
+    )]
+    pub campaign: Account<'info, Campaign>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRefundPolicy<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = !campaign.is_finalized @ ErrorCode::CampaignFinalized,
+        constraint = !campaign.is_cancelled @ ErrorCode::CampaignCancelled,
+        constraint = !campaign.is_failed @ ErrorCode::CampaignFailed,
+    )]
+    pub campaign: Account<'info, Campaign>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateTarget<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = !campaign.is_finalized @ ErrorCode::CampaignFinalized,
+        constraint = !campaign.is_cancelled @ ErrorCode::CampaignCancelled,
+        constraint = !campaign.is_failed @ ErrorCode::CampaignFailed,
+        constraint = !campaign.overflow_in_progress @ ErrorCode::OverflowDistributionInProgress,
+    )]
+    pub campaign: Account<'info, Campaign>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateBeneficiary<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = !campaign.is_finalized @ ErrorCode::CampaignFinalized,
+        constraint = !campaign.is_cancelled @ ErrorCode::CampaignCancelled,
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    /// CHECK: we only read the pubkey and store it
+    pub new_beneficiary: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"protocol_config"], bump)]
+    /// CHECK: may be uninitialized when the protocol has no config
+    pub protocol_config: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"beneficiary_registry", new_beneficiary.key().as_ref()], bump)]
+    /// CHECK: may be uninitialized when the beneficiary is not registered
+    pub beneficiary_registry: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Cancel<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = !campaign.is_finalized @ ErrorCode::CampaignFinalized,
+        constraint = !campaign.is_cancelled @ ErrorCode::CampaignCancelled,
+        constraint = !campaign.is_failed @ ErrorCode::CampaignFailed,
+        // Half-paid overflow shares would skew the refunds
+        constraint = !campaign.overflow_in_progress @ ErrorCode::OverflowDistributionInProgress,
+        // Withdrawn lamports are gone, so every refund claim could not be met
+        constraint = campaign.total_withdrawn == 0 @ ErrorCode::FundsWithdrawn,
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    #[account(
+        mut,
+        seeds = [b"creator_profile", campaign.authority.as_ref()],
+        bump = creator_profile.bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+}
+
+#[derive(Accounts)]
+pub struct ProcessDeadline<'info> {
+    #[account(mut, has_one = beneficiary, has_one = authority)]
+    pub campaign: Account<'info, Campaign>,
+
+    /// CHECK: matched against campaign.beneficiary; receives an auto-finalize payout
+    #[account(mut)]
+    pub beneficiary: UncheckedAccount<'info>,
+
+    /// Campaign creator (gets vault remainder on close)
+    #[account(mut)]
+    pub authority: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_lamports", campaign.key().as_ref()],
+        bump
+    )]
+    /// CHECK: system-owned PDA used only for lamport transfers
+    pub vault_lamports: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    #[account(
+        mut,
+        seeds = [b"creator_profile", authority.key().as_ref()],
+        bump = creator_profile.bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+
+    #[account(seeds = [b"protocol_config"], bump)]
+    /// CHECK: may be uninitialized when the protocol has no config
+    pub protocol_config: UncheckedAccount<'info>,
+
+    /// CHECK: must be protocol_config.treasury when a rent split applies
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExtendDeadline<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = !campaign.is_finalized @ ErrorCode::CampaignFinalized,
+        constraint = !campaign.is_cancelled @ ErrorCode::CampaignCancelled,
+        constraint = !campaign.is_failed @ ErrorCode::CampaignFailed,
+    )]
+    pub campaign: Account<'info, Campaign>,
+}
+
+#[derive(Accounts)]
+pub struct MarkFailed<'info> {
+    #[account(
+        mut,
+        constraint = !campaign.is_finalized @ ErrorCode::CampaignFinalized,
+        constraint = !campaign.is_cancelled @ ErrorCode::CampaignCancelled,
+        constraint = !campaign.is_failed @ ErrorCode::CampaignFailed,
+        // Withdrawn lamports are gone, so every refund claim could not be met
+        constraint = campaign.total_withdrawn == 0 @ ErrorCode::FundsWithdrawn,
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    #[account(
+        mut,
+        seeds = [b"creator_profile", campaign.authority.as_ref()],
+        bump = creator_profile.bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+}
+
+#[derive(Accounts)]
+pub struct RaiseDispute<'info> {
+    pub donor: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = !campaign.is_finalized @ ErrorCode::CampaignFinalized,
+        constraint = !campaign.is_cancelled @ ErrorCode::CampaignCancelled,
+        constraint = !campaign.is_failed @ ErrorCode::CampaignFailed,
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        mut,
+        has_one = campaign,
+        has_one = donor,
+        seeds = [b"contribution", campaign.key().as_ref(), donor.key().as_ref()],
+        bump = contribution.bump,
+        constraint = contribution.amount > 0 @ ErrorCode::NotADonor,
+    )]
+    pub contribution: Account<'info, Contribution>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    pub arbiter: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = arbiter @ ErrorCode::Unauthorized,
+        constraint = !campaign.is_finalized @ ErrorCode::CampaignFinalized,
+        constraint = !campaign.is_cancelled @ ErrorCode::CampaignCancelled,
+        constraint = !campaign.is_failed @ ErrorCode::CampaignFailed,
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    #[account(
+        mut,
+        seeds = [b"creator_profile", campaign.authority.as_ref()],
+        bump = creator_profile.bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimBond<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(mut, has_one = authority)]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(mut, seeds = [b"creator_bond", campaign.key().as_ref()], bump)]
+    /// CHECK: system-owned PDA holding the creator bond
+    pub creator_bond: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SlashBond<'info> {
+    pub slasher: Signer<'info>,
+
+    #[account(mut)]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(mut, seeds = [b"creator_bond", campaign.key().as_ref()], bump)]
+    /// CHECK: system-owned PDA holding the creator bond
+    pub creator_bond: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"protocol_config"], bump)]
+    /// CHECK: may be uninitialized when the protocol has no config
+    pub protocol_config: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReadGlobalStats<'info> {
+    #[account(seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+}
+
+#[derive(Accounts)]
+#[instruction(subject: Pubkey)]
+pub struct IssueSocialAttestation<'info> {
+    #[account(mut)]
+    pub issuer: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = issuer,
+        space = 8 + SocialAttestation::INIT_SPACE,
+        seeds = [b"social_attestation", issuer.key().as_ref(), subject.as_ref()],
+        bump
+    )]
+    pub attestation: Account<'info, SocialAttestation>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeSocialAttestation<'info> {
+    #[account(mut)]
+    pub issuer: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = issuer,
+        seeds = [b"social_attestation", issuer.key().as_ref(), attestation.subject.as_ref()],
+        bump = attestation.bump,
+        close = issuer,
+    )]
+    pub attestation: Account<'info, SocialAttestation>,
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(mut)]
+    pub donor: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = beneficiary,
+        seeds = [b"campaign", campaign.authority.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    /// CHECK: receives the streamed share in stream_payout mode
+    #[account(mut)]
+    pub beneficiary: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_lamports", campaign.key().as_ref()],
+        bump
+    )]
+    /// CHECK: system-owned PDA used only for lamport transfers
+    pub vault_lamports: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = donor,
+        space = 8 + Contribution::INIT_SPACE,
+        seeds = [b"contribution", campaign.key().as_ref(), donor.key().as_ref()],
+        bump
+    )]
+    pub contribution: Account<'info, Contribution>,
+
+    #[account(
+        seeds = [
+            b"social_attestation",
+            campaign.social_issuer.as_ref(),
+            donor.key().as_ref(),
+        ],
+        bump
+    )]
+    /// CHECK: may be uninitialized when the campaign is not social-gated
+    pub social_attestation: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = donor,
+        space = 8 + DonorProfile::INIT_SPACE,
+        seeds = [b"donor_profile", donor.key().as_ref()],
+        bump
+    )]
+    pub donor_profile: Account<'info, DonorProfile>,
+
+    pub system_program: Program<'info, System>
+
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64, commitment: [u8; 32])]
+pub struct DepositAnonymous<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.authority.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_lamports", campaign.key().as_ref()],
+        bump
+    )]
+    /// CHECK: system-owned PDA used only for lamport transfers
+    pub vault_lamports: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + AnonymousDeposit::INIT_SPACE,
+        seeds = [b"anonymous_deposit", campaign.key().as_ref(), commitment.as_ref()],
+        bump
+    )]
+    pub anonymous_deposit: Account<'info, AnonymousDeposit>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevealContribution<'info> {
+    #[account(mut)]
+    pub donor: Signer<'info>,
+
+    /// A finalized campaign's contributions may already be closed by
+    /// `finalize_and_cleanup`, so none is created afterwards
+    #[account(
+        mut,
+        constraint = !campaign.is_finalized @ ErrorCode::CampaignFinalized,
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    /// Closed into the donor's contribution; its rent goes to the donor
+    #[account(
+        mut,
+        close = donor,
+        has_one = campaign,
+        seeds = [b"anonymous_deposit", campaign.key().as_ref(), anonymous_deposit.commitment.as_ref()],
+        bump = anonymous_deposit.bump
+    )]
+    pub anonymous_deposit: Account<'info, AnonymousDeposit>,
+
+    #[account(
+        init_if_needed,
+        payer = donor,
+        space = 8 + Contribution::INIT_SPACE,
+        seeds = [b"contribution", campaign.key().as_ref(), donor.key().as_ref()],
+        bump
+    )]
+    pub contribution: Account<'info, Contribution>,
+
+    #[account(
+        init_if_needed,
+        payer = donor,
+        space = 8 + DonorProfile::INIT_SPACE,
+        seeds = [b"donor_profile", donor.key().as_ref()],
+        bump
+    )]
+    pub donor_profile: Account<'info, DonorProfile>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for `deposit_cpi`, called by another program:
+/// - `donor`: the calling program's PDA, signed with its seeds; keys the contribution
+/// - `payer`: system-owned account funding the deposit and the contribution rent
+#[derive(Accounts)]
+pub struct DepositCpi<'info> {
+    pub donor: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.authority.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_lamports", campaign.key().as_ref()],
+        bump
+    )]
+    /// CHECK: system-owned PDA used only for lamport transfers
+    pub vault_lamports: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + Contribution::INIT_SPACE,
+        seeds = [b"contribution", campaign.key().as_ref(), donor.key().as_ref()],
+        bump
+    )]
+    pub contribution: Account<'info, Contribution>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + DonorProfile::INIT_SPACE,
+        seeds = [b"donor_profile", donor.key().as_ref()],
+        bump
+    )]
+    pub donor_profile: Account<'info, DonorProfile>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Refund<'info> {
+    #[account(mut)]
+    pub donor: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = !campaign.is_finalized @ ErrorCode::CampaignFinalized,
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_lamports", campaign.key().as_ref()],
+        bump
+    )]
+    /// CHECK: system-owned PDA used only for lamport transfers
+    pub vault_lamports: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"contribution", campaign.key().as_ref(), donor.key().as_ref()],
+        bump = contribution.bump
+    )]
+    pub contribution: Account<'info, Contribution>,
+
+    #[account(
+        init_if_needed,
+        payer = donor,
+        space = 8 + DonorProfile::INIT_SPACE,
+        seeds = [b"donor_profile", donor.key().as_ref()],
+        bump
+    )]
+    pub donor_profile: Account<'info, DonorProfile>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DistributeOverflow<'info> {
+    #[account(
+        mut,
+        has_one = beneficiary,
+        constraint = !campaign.is_finalized @ ErrorCode::CampaignFinalized,
+        constraint = !campaign.is_cancelled @ ErrorCode::CampaignCancelled,
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    /// CHECK: receives overflow dust; matched against campaign.beneficiary
+    #[account(mut)]
+    pub beneficiary: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_lamports", campaign.key().as_ref()],
+        bump
+    )]
+    /// CHECK: system-owned PDA used only for lamport transfers
+    pub vault_lamports: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AuthorizeDeposit<'info> {
+    #[account(mut)]
+    pub donor: Signer<'info>,
+
+    #[account(constraint = !campaign.is_finalized @ ErrorCode::CampaignFinalized)]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        init,
+        payer = donor,
+        space = 8 + DepositAuthorization::INIT_SPACE,
+        seeds = [b"deposit_authorization", campaign.key().as_ref(), donor.key().as_ref()],
+        bump
+    )]
+    pub authorization: Account<'info, DepositAuthorization>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DepositDelegated<'info> {
+    /// Submits the deposit and pays for the contribution account if needed
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    /// Receives the authorization's leftover lamports
+    #[account(mut)]
+    pub donor: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.authority.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_lamports", campaign.key().as_ref()],
+        bump
+    )]
+    /// CHECK: system-owned PDA used only for lamport transfers
+    pub vault_lamports: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"deposit_authorization", campaign.key().as_ref(), donor.key().as_ref()],
+        bump = authorization.bump,
+        constraint = authorization.relayer == relayer.key() @ ErrorCode::AuthorizationMismatch,
+        close = donor,
+    )]
+    pub authorization: Account<'info, DepositAuthorization>,
+
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = 8 + Contribution::INIT_SPACE,
+        seeds = [b"contribution", campaign.key().as_ref(), donor.key().as_ref()],
+        bump
+    )]
+    pub contribution: Account<'info, Contribution>,
+
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = 8 + DonorProfile::INIT_SPACE,
+        seeds = [b"donor_profile", donor.key().as_ref()],
+        bump
+    )]
+    pub donor_profile: Account<'info, DonorProfile>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeDepositAuthorization<'info> {
+    #[account(mut)]
+    pub donor: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = donor,
+        seeds = [b"deposit_authorization", authorization.campaign.as_ref(), donor.key().as_ref()],
+        bump = authorization.bump,
+        close = donor,
+    )]
+    pub authorization: Account<'info, DepositAuthorization>,
+}
+
+#[derive(Accounts)]
+pub struct FundVoucherEscrow<'info> {
+    #[account(mut)]
+    pub donor: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = donor,
+        space = 8 + VoucherEscrow::INIT_SPACE,
+        seeds = [b"voucher_escrow", donor.key().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, VoucherEscrow>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseVoucherEscrow<'info> {
+    #[account(mut)]
+    pub donor: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = donor,
+        seeds = [b"voucher_escrow", donor.key().as_ref()],
+        bump = escrow.bump,
+        close = donor,
+    )]
+    pub escrow: Account<'info, VoucherEscrow>,
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64, nonce: u64)]
+pub struct DepositVoucher<'info> {
+    /// Submits the voucher and pays for the nonce and contribution accounts
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    /// CHECK: the voucher signer; authenticated by the ed25519 instruction
+    pub donor: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.authority.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_lamports", campaign.key().as_ref()],
+        bump
+    )]
+    /// CHECK: system-owned PDA used only for lamport transfers
+    pub vault_lamports: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        has_one = donor,
+        seeds = [b"voucher_escrow", donor.key().as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Account<'info, VoucherEscrow>,
+
+    /// Fails to initialize when the nonce was already spent
+    #[account(
+        init,
+        payer = relayer,
+        space = 8 + VoucherNonce::INIT_SPACE,
+        seeds = [
+            b"voucher_nonce",
+            campaign.key().as_ref(),
+            donor.key().as_ref(),
+            &nonce.to_le_bytes(),
+        ],
+        bump
+    )]
+    pub voucher_nonce: Account<'info, VoucherNonce>,
+
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = 8 + Contribution::INIT_SPACE,
+        seeds = [b"contribution", campaign.key().as_ref(), donor.key().as_ref()],
+        bump
+    )]
+    pub contribution: Account<'info, Contribution>,
+
+    #[account(address = instructions_sysvar::ID)]
+    /// CHECK: instructions sysvar, read for the ed25519 verification
+    pub instructions: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = 8 + DonorProfile::INIT_SPACE,
+        seeds = [b"donor_profile", donor.key().as_ref()],
+        bump
+    )]
+    pub donor_profile: Account<'info, DonorProfile>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RequestLargeDeposit<'info> {
+    #[account(mut)]
+    pub donor: Signer<'info>,
+
+    #[account(
+        constraint = !campaign.is_finalized @ ErrorCode::CampaignFinalized,
+        constraint = !campaign.is_cancelled @ ErrorCode::CampaignCancelled,
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        init,
+        payer = donor,
+        space = 8 + PendingDeposit::INIT_SPACE,
+        seeds = [b"pending_deposit", campaign.key().as_ref(), donor.key().as_ref()],
+        bump
+    )]
+    pub pending_deposit: Account<'info, PendingDeposit>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfirmLargeDeposit<'info> {
+    #[account(mut)]
+    pub donor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.authority.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_lamports", campaign.key().as_ref()],
+        bump
+    )]
+    /// CHECK: system-owned PDA used only for lamport transfers
+    pub vault_lamports: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pending_deposit", campaign.key().as_ref(), donor.key().as_ref()],
+        bump = pending_deposit.bump,
+        close = donor,
+    )]
+    pub pending_deposit: Account<'info, PendingDeposit>,
+
+    #[account(
+        init_if_needed,
+        payer = donor,
+        space = 8 + Contribution::INIT_SPACE,
+        seeds = [b"contribution", campaign.key().as_ref(), donor.key().as_ref()],
+        bump
+    )]
+    pub contribution: Account<'info, Contribution>,
+
+    #[account(
+        init_if_needed,
+        payer = donor,
+        space = 8 + DonorProfile::INIT_SPACE,
+        seeds = [b"donor_profile", donor.key().as_ref()],
+        bump
+    )]
+    pub donor_profile: Account<'info, DonorProfile>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelLargeDeposit<'info> {
+    #[account(mut)]
+    pub donor: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = donor,
+        seeds = [b"pending_deposit", pending_deposit.campaign.as_ref(), donor.key().as_ref()],
+        bump = pending_deposit.bump,
+        close = donor,
+    )]
+    pub pending_deposit: Account<'info, PendingDeposit>,
+}
+
+#[derive(Accounts)]
+pub struct FundMatch<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = !campaign.is_finalized @ ErrorCode::CampaignFinalized,
+        constraint = !campaign.is_cancelled @ ErrorCode::CampaignCancelled,
+        constraint = !campaign.is_failed @ ErrorCode::CampaignFailed,
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_lamports", campaign.key().as_ref()],
+        bump
+    )]
+    /// CHECK: system-owned PDA used only for lamport transfers
+    pub vault_lamports: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReclaimMatch<'info> {
+    /// Campaign authority, who funds the match pool
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(mut, has_one = authority)]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_lamports", campaign.key().as_ref()],
+        bump
+    )]
+    /// CHECK: system-owned PDA used only for lamport transfers
+    pub vault_lamports: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeAndReward<'info> {
+    /// Beneficiary must authorize finalization and pays for the reward mint
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+
+    /// Campaign creator (gets vault remainder on close)
+    #[account(mut)]
+    pub authority: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        has_one = beneficiary,
+        has_one = authority,
+        constraint = !campaign.is_finalized @ ErrorCode::CampaignFinalized,
+        constraint = !campaign.is_cancelled @ ErrorCode::CampaignCancelled,
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_lamports", campaign.key().as_ref()],
+        bump
+    )]
+    /// CHECK: system-owned PDA used only for lamport transfers
+    pub vault_lamports: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"reward_mint", campaign.key().as_ref()],
+        bump
+    )]
+    /// CHECK: created here as an SPL Token mint owned by the token program
+    pub reward_mint: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    #[account(
+        mut,
+        seeds = [b"creator_profile", authority.key().as_ref()],
+        bump = creator_profile.bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+
+    #[account(seeds = [b"protocol_config"], bump)]
+    /// CHECK: may be uninitialized when the protocol has no config
+    pub protocol_config: UncheckedAccount<'info>,
+
+    /// CHECK: must be protocol_config.treasury when a rent split applies
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// CHECK: address-checked SPL Token program
+    #[account(address = TOKEN_PROGRAM_ID)]
+    pub token_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimReward<'info> {
+    pub donor: Signer<'info>,
+
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        mut,
+        has_one = campaign,
+        has_one = donor,
+        seeds = [b"contribution", campaign.key().as_ref(), donor.key().as_ref()],
+        bump = contribution.bump
+    )]
+    pub contribution: Account<'info, Contribution>,
+
+    #[account(
+        mut,
+        seeds = [b"reward_mint", campaign.key().as_ref()],
+        bump = campaign.reward_mint_bump
+    )]
+    /// CHECK: the campaign's reward mint, validated by the token program on MintTo
+    pub reward_mint: UncheckedAccount<'info>,
+
+    /// CHECK: token account for the reward mint; the token program checks its mint
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+
+    /// CHECK: address-checked SPL Token program
+    #[account(address = TOKEN_PROGRAM_ID)]
+    pub token_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = beneficiary,
+        constraint = !campaign.is_finalized @ ErrorCode::CampaignFinalized,
+        constraint = !campaign.is_cancelled @ ErrorCode::CampaignCancelled,
+        constraint = !campaign.is_failed @ ErrorCode::CampaignFailed,
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_lamports", campaign.key().as_ref()],
+        bump
+    )]
+    /// CHECK: system-owned PDA used only for lamport transfers
+    pub vault_lamports: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
 pub struct Finalize<'info> {
     /// Beneficiary must authorize finalization
     #[account(mut)]
@@ -230,9 +5045,390 @@ pub struct Finalize<'info> {
         has_one = beneficiary,
         has_one = authority,
         constraint = !campaign.is_finalized @ ErrorCode::CampaignFinalized,
+        constraint = !campaign.is_cancelled @ ErrorCode::CampaignCancelled,
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_lamports", campaign.key().as_ref()],
+        bump
+    )]
+    /// CHECK: system-owned PDA used only for lamport transfers
+    pub vault_lamports: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    #[account(
+        mut,
+        seeds = [b"creator_profile", authority.key().as_ref()],
+        bump = creator_profile.bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+
+    #[account(seeds = [b"protocol_config"], bump)]
+    /// CHECK: may be uninitialized when the protocol has no config
+    pub protocol_config: UncheckedAccount<'info>,
+
+    /// CHECK: must be protocol_config.treasury when a rent split applies
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CrankFinalize<'info> {
+    /// Anyone; tipped with the rent excess under cranker_reward
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = beneficiary,
+        has_one = authority,
+        constraint = !campaign.is_finalized @ ErrorCode::CampaignFinalized,
+        constraint = !campaign.is_cancelled @ ErrorCode::CampaignCancelled,
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    /// CHECK: matched against campaign.beneficiary; receives the payout
+    #[account(mut)]
+    pub beneficiary: UncheckedAccount<'info>,
+
+    /// Campaign creator (gets the fee, and the vault remainder unless the cranker is tipped)
+    #[account(mut)]
+    pub authority: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_lamports", campaign.key().as_ref()],
+        bump
+    )]
+    /// CHECK: system-owned PDA used only for lamport transfers
+    pub vault_lamports: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    #[account(
+        mut,
+        seeds = [b"creator_profile", authority.key().as_ref()],
+        bump = creator_profile.bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+
+    #[account(seeds = [b"protocol_config"], bump)]
+    /// CHECK: may be uninitialized when the protocol has no config
+    pub protocol_config: UncheckedAccount<'info>,
+
+    /// CHECK: must be protocol_config.treasury when a rent split applies
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeAndCleanup<'info> {
+    /// Beneficiary must authorize finalization
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+
+    /// Campaign creator (gets the vault remainder and the closed accounts' rent)
+    #[account(mut)]
+    pub authority: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        has_one = beneficiary,
+        has_one = authority,
+        constraint = !campaign.is_cancelled @ ErrorCode::CampaignCancelled,
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_lamports", campaign.key().as_ref()],
+        bump
+    )]
+    /// CHECK: system-owned PDA used only for lamport transfers
+    pub vault_lamports: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    #[account(
+        mut,
+        seeds = [b"creator_profile", authority.key().as_ref()],
+        bump = creator_profile.bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+
+    #[account(seeds = [b"protocol_config"], bump)]
+    /// CHECK: may be uninitialized when the protocol has no config
+    pub protocol_config: UncheckedAccount<'info>,
+
+    /// CHECK: must be protocol_config.treasury when a rent split applies
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeTeamVested<'info> {
+    /// Beneficiary must authorize finalization and pays for the vesting account
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+
+    /// Campaign creator (gets vault remainder on close)
+    #[account(mut)]
+    pub authority: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        has_one = beneficiary,
+        has_one = authority,
+        constraint = !campaign.is_finalized @ ErrorCode::CampaignFinalized,
+        constraint = !campaign.is_cancelled @ ErrorCode::CampaignCancelled,
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_lamports", campaign.key().as_ref()],
+        bump
+    )]
+    /// CHECK: system-owned PDA used only for lamport transfers
+    pub vault_lamports: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = beneficiary,
+        space = 8 + TeamVesting::INIT_SPACE,
+        seeds = [b"team_vesting", campaign.key().as_ref()],
+        bump
+    )]
+    pub team_vesting: Account<'info, TeamVesting>,
+
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    #[account(
+        mut,
+        seeds = [b"creator_profile", authority.key().as_ref()],
+        bump = creator_profile.bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+
+    #[account(seeds = [b"protocol_config"], bump)]
+    /// CHECK: may be uninitialized when the protocol has no config
+    pub protocol_config: UncheckedAccount<'info>,
+
+    /// CHECK: must be protocol_config.treasury when a rent split applies
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    #[account(mut)]
+    pub member: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"team_vesting", team_vesting.campaign.as_ref()],
+        bump = team_vesting.bump
+    )]
+    pub team_vesting: Account<'info, TeamVesting>,
+
+    #[account(
+        init_if_needed,
+        payer = member,
+        space = 8 + VestingStream::INIT_SPACE,
+        seeds = [b"vesting_stream", team_vesting.key().as_ref(), member.key().as_ref()],
+        bump
+    )]
+    pub vesting_stream: Account<'info, VestingStream>,
+
+    #[account(seeds = [b"protocol_config"], bump)]
+    /// CHECK: may be uninitialized when the protocol has no config
+    pub protocol_config: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"beneficiary_registry", member.key().as_ref()], bump)]
+    /// CHECK: may be uninitialized when the member is not registered
+    pub beneficiary_registry: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeToBackup<'info> {
+    /// Backup beneficiary must authorize and receives the funds
+    #[account(mut)]
+    pub backup_beneficiary: Signer<'info>,
+
+    /// Campaign creator (gets vault remainder on close)
+    #[account(mut)]
+    pub authority: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        has_one = backup_beneficiary @ ErrorCode::Unauthorized,
+        has_one = authority,
+        constraint = !campaign.is_finalized @ ErrorCode::CampaignFinalized,
+        constraint = !campaign.is_cancelled @ ErrorCode::CampaignCancelled,
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_lamports", campaign.key().as_ref()],
+        bump
+    )]
+    /// CHECK: system-owned PDA used only for lamport transfers
+    pub vault_lamports: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [b"global_stats"], bump = global_stats.bump)]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    #[account(
+        mut,
+        seeds = [b"creator_profile", authority.key().as_ref()],
+        bump = creator_profile.bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+
+    #[account(seeds = [b"protocol_config"], bump)]
+    /// CHECK: may be uninitialized when the protocol has no config
+    pub protocol_config: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"beneficiary_registry", backup_beneficiary.key().as_ref()], bump)]
+    /// CHECK: may be uninitialized when the backup is not registered
+    pub beneficiary_registry: UncheckedAccount<'info>,
+
+    /// CHECK: must be protocol_config.treasury when a rent split applies
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReadContribution<'info> {
+    pub campaign: Account<'info, Campaign>,
+
+    /// CHECK: only used as a seed for the contribution PDA
+    pub donor: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"contribution", campaign.key().as_ref(), donor.key().as_ref()],
+        bump
+    )]
+    /// CHECK: may be uninitialized when the donor never contributed
+    pub contribution: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UnlockContent<'info> {
+    pub donor: Signer<'info>,
+
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        seeds = [b"contribution", campaign.key().as_ref(), donor.key().as_ref()],
+        bump
     )]
+    /// CHECK: may be uninitialized when the donor never contributed
+    pub contribution: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PostComment<'info> {
+    pub donor: Signer<'info>,
+
+    #[account(mut)]
     pub campaign: Account<'info, Campaign>,
 
+    #[account(
+        has_one = campaign,
+        has_one = donor,
+        seeds = [b"contribution", campaign.key().as_ref(), donor.key().as_ref()],
+        bump = contribution.bump,
+        constraint = contribution.amount > 0 @ ErrorCode::NotADonor,
+    )]
+    pub contribution: Account<'info, Contribution>,
+}
+
+#[derive(Accounts)]
+pub struct InitProtocolConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + ProtocolConfig::INIT_SPACE,
+        seeds = [b"protocol_config"],
+        bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::TestProject>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(admin.key())
+            @ ErrorCode::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateProtocolConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(mut, seeds = [b"protocol_config"], bump = protocol_config.bump, has_one = admin)]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(beneficiary: Pubkey)]
+pub struct SetBeneficiaryAllowed<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"protocol_config"], bump = protocol_config.bump, has_one = admin)]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + BeneficiaryRegistry::INIT_SPACE,
+        seeds = [b"beneficiary_registry", beneficiary.as_ref()],
+        bump
+    )]
+    pub beneficiary_registry: Account<'info, BeneficiaryRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct EmergencyReclaim<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"protocol_config"], bump = protocol_config.bump, has_one = admin)]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+
+    /// CHECK: must be a closed campaign address, verified in the handler
+    pub campaign: UncheckedAccount<'info>,
+
     #[account(
         mut,
         seeds = [b"vault_lamports", campaign.key().as_ref()],
@@ -241,5 +5437,115 @@ pub struct Finalize<'info> {
     /// CHECK: system-owned PDA used only for lamport transfers
     pub vault_lamports: UncheckedAccount<'info>,
 
+    #[account(mut)]
+    pub recovery: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Archive<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = campaign.is_finalized @ ErrorCode::CampaignNotFinalized,
+        // Contributions outliving the campaign would still hold amounts
+        constraint = campaign.fully_cleaned || campaign.contribution_accounts == 0
+            @ ErrorCode::CampaignNotCleaned,
+        // The bond PDA can only be released through the campaign
+        constraint = campaign.bond_state != BOND_POSTED && campaign.bond_state != BOND_FORFEITED
+            @ ErrorCode::BondOutstanding,
+        close = authority,
+    )]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ArchivedCampaign::INIT_SPACE,
+        seeds = [b"archived_campaign", campaign.key().as_ref()],
+        bump
+    )]
+    pub archived_campaign: Account<'info, ArchivedCampaign>,
+
     pub system_program: Program<'info, System>,
 }
+
+#[derive(Accounts)]
+pub struct AssertInvariants<'info> {
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        seeds = [b"vault_lamports", campaign.key().as_ref()],
+        bump
+    )]
+    /// CHECK: system-owned PDA, only its balance is read
+    pub vault_lamports: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReadCreatorProfile<'info> {
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        seeds = [b"creator_profile", campaign.authority.as_ref()],
+        bump = creator_profile.bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+}
+
+#[derive(Accounts)]
+pub struct ReadDonorProfile<'info> {
+    /// CHECK: only used as a seed for the donor profile PDA
+    pub donor: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"donor_profile", donor.key().as_ref()], bump)]
+    /// CHECK: may be uninitialized when the donor never used `deposit`
+    pub donor_profile: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReadCampaign<'info> {
+    pub campaign: Account<'info, Campaign>,
+}
+
+#[derive(Accounts)]
+pub struct ImportState<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(mut, has_one = authority)]
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        seeds = [b"vault_lamports", campaign.key().as_ref()],
+        bump
+    )]
+    /// CHECK: system-owned PDA, only its balance is read
+    pub vault_lamports: UncheckedAccount<'info>,
+
+    /// CHECK: must be the imported beneficiary, only its key is used
+    pub beneficiary: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"protocol_config"], bump)]
+    /// CHECK: may be uninitialized when the protocol has no config
+    pub protocol_config: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"beneficiary_registry", beneficiary.key().as_ref()], bump)]
+    /// CHECK: may be uninitialized when the beneficiary is not registered
+    pub beneficiary_registry: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReadVault<'info> {
+    pub campaign: Account<'info, Campaign>,
+
+    #[account(
+        seeds = [b"vault_lamports", campaign.key().as_ref()],
+        bump
+    )]
+    /// CHECK: system-owned PDA, only its balance is read
+    pub vault_lamports: UncheckedAccount<'info>,
+}
@@ -1,12 +1,17 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import {
+  Ed25519Program,
   Keypair,
   PublicKey,
   SystemProgram,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
   LAMPORTS_PER_SOL,
+  Transaction,
+  TransactionInstruction,
 } from "@solana/web3.js";
 import { assert } from "chai";
+import { createHash, randomBytes } from "crypto";
 
 import { TestProject } from "../target/types/test_project";
 
@@ -47,24 +52,106 @@ function findVaultLamportsPda(campaignPda: PublicKey): PublicKey {
   return pda;
 }
 
+function findGlobalStatsPda(): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("global_stats")],
+    program.programId
+  );
+  return pda;
+}
+
+function findContributionPda(
+  campaignPda: PublicKey,
+  donor: PublicKey
+): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("contribution"), campaignPda.toBuffer(), donor.toBuffer()],
+    program.programId
+  );
+  return pda;
+}
+
+function defaultRefundPolicy() {
+  return {
+    refundsEnabled: false,
+    refundPenaltyBps: 0,
+    refundWindow: new anchor.BN(0),
+    lockupSeconds: new anchor.BN(0),
+    maxTotalRefunds: new anchor.BN(0),
+    immutable: false,
+  };
+}
+
+function defaultConfig() {
+  return {
+    matchTiers: Array.from({ length: 3 }, () => ({
+      amount: new anchor.BN(0),
+      rateBps: 0,
+    })),
+    strictTarget: false,
+    refundPolicy: defaultRefundPolicy(),
+    largeDepositThreshold: new anchor.BN(0),
+    deadline: new anchor.BN(0),
+    backupBeneficiary: PublicKey.default,
+    backupDelay: new anchor.BN(0),
+    allowedAmounts: [0, 0, 0, 0].map((amount) => new anchor.BN(amount)),
+    feeBps: 0,
+    blockBeneficiaryDeposits: false,
+    team: Array.from({ length: 4 }, () => ({ member: PublicKey.default, shareBps: 0 })),
+    endAction: 0,
+    verboseLogs: false,
+    allowPartialWithdrawals: false,
+    withdrawFloor: new anchor.BN(0),
+    withdrawReserve: new anchor.BN(0),
+    minFinalizeAmount: new anchor.BN(0),
+    maxPerDonor: new anchor.BN(0),
+    rewardRate: new anchor.BN(0),
+    overshootToleranceBps: 0,
+    roundUpToTier: false,
+    maxTotalWithdrawals: new anchor.BN(0),
+    gatedContentHash: Array.from({ length: 32 }, () => 0),
+    unlockThreshold: new anchor.BN(0),
+    streamPayout: false,
+    streamBps: 0,
+    minDepositCount: 0,
+    arbiter: PublicKey.default,
+    dustRefundThreshold: new anchor.BN(0),
+    requireSocial: false,
+    socialIssuer: PublicKey.default,
+    bondAmount: new anchor.BN(0),
+    minSlotGap: new anchor.BN(0),
+    crankerReward: false,
+  };
+}
+
+type CampaignConfig = ReturnType<typeof defaultConfig>;
+
 async function initCampaign(params: {
   creator: Keypair;
   beneficiary: PublicKey;
   targetLamports: number;
+  config?: Partial<CampaignConfig>;
 }) {
   const campaignId = nextCampaignId();
   const target = new anchor.BN(params.targetLamports);
+  const config = { ...defaultConfig(), ...params.config };
 
   const campaignPda = findCampaignPda(params.creator.publicKey, campaignId);
   const vaultLamportsPda = findVaultLamportsPda(campaignPda);
 
   await program.methods
-    .initialize(campaignId, target)
+    .initialize(campaignId, target, config)
     .accounts({
       creator: params.creator.publicKey,
       beneficiary: params.beneficiary,
       campaign: campaignPda,
       vaultLamports: vaultLamportsPda,
+      creatorBond: findCreatorBondPda(campaignPda),
+      globalStats: findGlobalStatsPda(),
+      creatorProfile: findCreatorProfilePda(params.creator.publicKey),
+      protocolConfig: findProtocolConfigPda(),
+      beneficiaryRegistry: findBeneficiaryRegistryPda(params.beneficiary),
+      archivedCampaign: findArchivedCampaignPda(campaignPda),
       systemProgram: SystemProgram.programId,
     })
     .rpc();
@@ -82,349 +169,5412 @@ async function expectError(p: Promise<unknown>, matcher: RegExp) {
   }
 }
 
-describe("test_project", () => {
-  const creator = (provider.wallet as anchor.Wallet).payer;
+async function campaignBeneficiary(campaignPda: PublicKey): Promise<PublicKey> {
+  return (await program.account.campaign.fetch(campaignPda)).beneficiary;
+}
 
-  it("initialize creates campaign + vault lamports PDA with expected state", async () => {
-    const beneficiary = Keypair.generate();
-    await airdrop(beneficiary.publicKey);
+function findSocialAttestationPda(issuer: PublicKey, subject: PublicKey): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("social_attestation"), issuer.toBuffer(), subject.toBuffer()],
+    program.programId
+  );
+  return pda;
+}
 
-    const targetLamports = Math.floor(0.05 * LAMPORTS_PER_SOL);
-    const { campaignPda, vaultLamportsPda } = await initCampaign({
-      creator,
+// Deposits always pass the attestation slot for the campaign's issuer, used or not
+async function socialAttestationFor(campaignPda: PublicKey, donor: PublicKey): Promise<PublicKey> {
+  const { socialIssuer } = await program.account.campaign.fetch(campaignPda);
+  return findSocialAttestationPda(socialIssuer, donor);
+}
+
+async function depositFrom(
+  donor: Keypair,
+  campaignPda: PublicKey,
+  vaultLamportsPda: PublicKey,
+  lamports: number
+) {
+  return program.methods
+    .deposit(new anchor.BN(lamports))
+    .accounts({
+      donor: donor.publicKey,
+      campaign: campaignPda,
+      beneficiary: await campaignBeneficiary(campaignPda),
+      vaultLamports: vaultLamportsPda,
+      contribution: findContributionPda(campaignPda, donor.publicKey),
+      socialAttestation: await socialAttestationFor(campaignPda, donor.publicKey),
+      donorProfile: findDonorProfilePda(donor.publicKey),
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([donor])
+    .rpc();
+}
+
+async function fundMatch(
+  authority: Keypair,
+  campaignPda: PublicKey,
+  vaultLamportsPda: PublicKey,
+  lamports: number
+) {
+  return program.methods
+    .fundMatch(new anchor.BN(lamports))
+    .accounts({
+      authority: authority.publicKey,
+      campaign: campaignPda,
+      vaultLamports: vaultLamportsPda,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([authority])
+    .rpc();
+}
+
+function findArchivedCampaignPda(campaignPda: PublicKey): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("archived_campaign"), campaignPda.toBuffer()],
+    program.programId
+  );
+  return pda;
+}
+
+async function finalizeBy(
+  beneficiary: Keypair,
+  authority: PublicKey,
+  campaignPda: PublicKey,
+  vaultLamportsPda: PublicKey
+) {
+  return program.methods
+    .finalize()
+    .accounts({
       beneficiary: beneficiary.publicKey,
-      targetLamports,
-    });
+      authority,
+      campaign: campaignPda,
+      vaultLamports: vaultLamportsPda,
+      globalStats: findGlobalStatsPda(),
+      creatorProfile: findCreatorProfilePda(authority),
+      protocolConfig: findProtocolConfigPda(),
+      treasury: await protocolTreasury(),
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([beneficiary])
+    .rpc();
+}
 
-    const campaign = await program.account.campaign.fetch(campaignPda);
-    assert.equal(campaign.funds.toString(), "0");
-    assert.equal(campaign.target.toString(), targetLamports.toString());
-    assert.equal(campaign.authority.toBase58(), creator.publicKey.toBase58());
-    assert.equal(
-      campaign.beneficiary.toBase58(),
-      beneficiary.publicKey.toBase58()
-    );
-    assert.equal(campaign.isFinalized, false);
+async function refundFrom(
+  donor: Keypair,
+  campaignPda: PublicKey,
+  vaultLamportsPda: PublicKey
+) {
+  return program.methods
+    .refund()
+    .accounts({
+      donor: donor.publicKey,
+      campaign: campaignPda,
+      vaultLamports: vaultLamportsPda,
+      contribution: findContributionPda(campaignPda, donor.publicKey),
+      donorProfile: findDonorProfilePda(donor.publicKey),
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([donor])
+    .rpc();
+}
 
-    const vaultLamportsInfo = await provider.connection.getAccountInfo(
-      vaultLamportsPda
-    );
-    assert.isNotNull(vaultLamportsInfo, "vault lamports should exist");
-    assert.equal(
-      vaultLamportsInfo?.owner.toBase58(),
-      SystemProgram.programId.toBase58()
-    );
-  });
+function findDepositAuthorizationPda(
+  campaignPda: PublicKey,
+  donor: PublicKey
+): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("deposit_authorization"),
+      campaignPda.toBuffer(),
+      donor.toBuffer(),
+    ],
+    program.programId
+  );
+  return pda;
+}
 
-  it("deposit increases campaign.funds and vault lamports", async () => {
-    const beneficiary = Keypair.generate();
-    await airdrop(beneficiary.publicKey);
+async function fetchGlobalStats() {
+  return program.methods
+    .getGlobalStats()
+    .accounts({ globalStats: findGlobalStatsPda() })
+    .view();
+}
 
-    const targetLamports = Math.floor(0.05 * LAMPORTS_PER_SOL);
-    const depositLamports = Math.floor(0.01 * LAMPORTS_PER_SOL);
+async function cancelCampaign(authority: Keypair, campaignPda: PublicKey) {
+  return program.methods
+    .cancel()
+    .accounts({
+      authority: authority.publicKey,
+      campaign: campaignPda,
+      globalStats: findGlobalStatsPda(),
+      creatorProfile: findCreatorProfilePda(authority.publicKey),
+    })
+    .signers([authority])
+    .rpc();
+}
 
-    const { campaignPda, vaultLamportsPda } = await initCampaign({
-      creator,
-      beneficiary: beneficiary.publicKey,
-      targetLamports,
-    });
+const BPF_LOADER_UPGRADEABLE_ID = new PublicKey(
+  "BPFLoaderUpgradeab1e11111111111111111111111"
+);
 
-    const donorBalanceBefore = await provider.connection.getBalance(
-      creator.publicKey
-    );
-    const vaultBalanceBefore = await provider.connection.getBalance(
-      vaultLamportsPda
-    );
+function findDonorProfilePda(donor: PublicKey): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("donor_profile"), donor.toBuffer()],
+    program.programId
+  );
+  return pda;
+}
 
-    await program.methods
-      .deposit(new anchor.BN(depositLamports))
-      .accounts({
-        donor: creator.publicKey,
-        campaign: campaignPda,
-        vaultLamports: vaultLamportsPda,
-        systemProgram: SystemProgram.programId,
-      })
-      .rpc();
+function findCreatorProfilePda(creator: PublicKey): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("creator_profile"), creator.toBuffer()],
+    program.programId
+  );
+  return pda;
+}
 
-    const campaign = await program.account.campaign.fetch(campaignPda);
-    assert.equal(campaign.funds.toString(), depositLamports.toString());
+function findProtocolConfigPda(): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("protocol_config")],
+    program.programId
+  );
+  return pda;
+}
 
-    const vaultBalanceAfter = await provider.connection.getBalance(
-      vaultLamportsPda
-    );
-    assert.equal(vaultBalanceAfter - vaultBalanceBefore, depositLamports);
+function findBeneficiaryRegistryPda(beneficiary: PublicKey): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("beneficiary_registry"), beneficiary.toBuffer()],
+    program.programId
+  );
+  return pda;
+}
 
-    const donorBalanceAfter = await provider.connection.getBalance(
-      creator.publicKey
-    );
-    assert.ok(
-      donorBalanceAfter <= donorBalanceBefore - depositLamports,
-      "donor should pay at least the deposit amount (plus fees)"
-    );
-  });
+// The provider wallet deploys the program on localnet, so it is the upgrade
+// authority and becomes the protocol admin.
+async function ensureProtocolConfig() {
+  const protocolConfig = findProtocolConfigPda();
+  if (await provider.connection.getAccountInfo(protocolConfig)) {
+    return protocolConfig;
+  }
+  const [programData] = PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    BPF_LOADER_UPGRADEABLE_ID
+  );
+  await program.methods
+    .initProtocolConfig()
+    .accounts({
+      admin: provider.wallet.publicKey,
+      protocolConfig,
+      program: program.programId,
+      programData,
+      systemProgram: SystemProgram.programId,
+    })
+    .rpc();
+  return protocolConfig;
+}
 
-  it("finalize transfers funds to beneficiary and closes vault", async () => {
-    const beneficiary = Keypair.generate();
-    await airdrop(beneficiary.publicKey);
+function findPendingDepositPda(
+  campaignPda: PublicKey,
+  donor: PublicKey
+): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("pending_deposit"), campaignPda.toBuffer(), donor.toBuffer()],
+    program.programId
+  );
+  return pda;
+}
 
-    const targetLamports = Math.floor(0.05 * LAMPORTS_PER_SOL);
-    const depositLamports = Math.floor(0.02 * LAMPORTS_PER_SOL);
+async function waitForSlot(slot: number) {
+  while ((await provider.connection.getSlot("confirmed")) < slot) {
+    await new Promise((resolve) => setTimeout(resolve, 200));
+  }
+}
 
-    const { campaignPda, vaultLamportsPda } = await initCampaign({
+async function setBeneficiaryWhitelist(enforce: boolean) {
+  return program.methods
+    .setBeneficiaryWhitelist(enforce)
+    .accounts({
+      admin: provider.wallet.publicKey,
+      protocolConfig: await ensureProtocolConfig(),
+    })
+    .rpc();
+}
+
+// Finalize paths need the current treasury; without a protocol config any account will do
+async function protocolTreasury(): Promise<PublicKey> {
+  const config = await program.account.protocolConfig.fetchNullable(findProtocolConfigPda());
+  return config ? config.treasury : provider.wallet.publicKey;
+}
+
+async function setRentSplit(treasury: PublicKey, rentSplitBps: number) {
+  return program.methods
+    .setRentSplit(treasury, rentSplitBps)
+    .accounts({
+      admin: provider.wallet.publicKey,
+      protocolConfig: await ensureProtocolConfig(),
+    })
+    .rpc();
+}
+
+async function waitForUnixTime(unixTimestamp: number) {
+  for (;;) {
+    const slot = await provider.connection.getSlot("confirmed");
+    const blockTime = await provider.connection.getBlockTime(slot);
+    if (blockTime !== null && blockTime > unixTimestamp) return;
+    await new Promise((resolve) => setTimeout(resolve, 500));
+  }
+}
+
+async function finalizeToBackup(
+  backup: Keypair,
+  authority: PublicKey,
+  campaignPda: PublicKey,
+  vaultLamportsPda: PublicKey
+) {
+  return program.methods
+    .finalizeToBackup()
+    .accounts({
+      backupBeneficiary: backup.publicKey,
+      authority,
+      campaign: campaignPda,
+      vaultLamports: vaultLamportsPda,
+      globalStats: findGlobalStatsPda(),
+      creatorProfile: findCreatorProfilePda(authority),
+      protocolConfig: findProtocolConfigPda(),
+      beneficiaryRegistry: findBeneficiaryRegistryPda(backup.publicKey),
+      treasury: await protocolTreasury(),
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([backup])
+    .rpc();
+}
+
+function findTeamVestingPda(campaignPda: PublicKey): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("team_vesting"), campaignPda.toBuffer()],
+    program.programId
+  );
+  return pda;
+}
+
+function findVestingStreamPda(teamVestingPda: PublicKey, member: PublicKey): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("vesting_stream"), teamVestingPda.toBuffer(), member.toBuffer()],
+    program.programId
+  );
+  return pda;
+}
+
+function teamConfig(members: { member: PublicKey; shareBps: number }[]): CampaignConfig {
+  const team = defaultConfig().team;
+  members.forEach((share, i) => (team[i] = share));
+  return { ...defaultConfig(), team };
+}
+
+async function finalizeTeamVested(
+  beneficiary: Keypair,
+  campaignPda: PublicKey,
+  vaultLamportsPda: PublicKey,
+  cliff: number,
+  duration: number
+) {
+  return program.methods
+    .finalizeTeamVested(new anchor.BN(cliff), new anchor.BN(duration))
+    .accounts({
+      beneficiary: beneficiary.publicKey,
+      authority: provider.wallet.publicKey,
+      campaign: campaignPda,
+      vaultLamports: vaultLamportsPda,
+      teamVesting: findTeamVestingPda(campaignPda),
+      globalStats: findGlobalStatsPda(),
+      creatorProfile: findCreatorProfilePda(provider.wallet.publicKey),
+      protocolConfig: findProtocolConfigPda(),
+      treasury: await protocolTreasury(),
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([beneficiary])
+    .rpc();
+}
+
+async function claimVested(member: Keypair, teamVestingPda: PublicKey) {
+  return program.methods
+    .claimVested()
+    .accounts({
+      member: member.publicKey,
+      teamVesting: teamVestingPda,
+      vestingStream: findVestingStreamPda(teamVestingPda, member.publicKey),
+      protocolConfig: findProtocolConfigPda(),
+      beneficiaryRegistry: findBeneficiaryRegistryPda(member.publicKey),
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([member])
+    .rpc();
+}
+
+function findAnonymousDepositPda(campaignPda: PublicKey, commitment: Buffer): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("anonymous_deposit"), campaignPda.toBuffer(), commitment],
+    program.programId
+  );
+  return pda;
+}
+
+function donorCommitment(donor: PublicKey, salt: Buffer): Buffer {
+  return createHash("sha256").update(donor.toBuffer()).update(salt).digest();
+}
+
+async function depositAnonymous(
+  payer: Keypair,
+  campaignPda: PublicKey,
+  vaultLamportsPda: PublicKey,
+  lamports: number,
+  commitment: Buffer
+) {
+  return program.methods
+    .depositAnonymous(new anchor.BN(lamports), [...commitment])
+    .accounts({
+      payer: payer.publicKey,
+      campaign: campaignPda,
+      vaultLamports: vaultLamportsPda,
+      anonymousDeposit: findAnonymousDepositPda(campaignPda, commitment),
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([payer])
+    .rpc();
+}
+
+async function revealContribution(
+  donor: Keypair,
+  campaignPda: PublicKey,
+  commitment: Buffer,
+  salt: Buffer
+) {
+  return program.methods
+    .revealContribution([...salt])
+    .accounts({
+      donor: donor.publicKey,
+      campaign: campaignPda,
+      anonymousDeposit: findAnonymousDepositPda(campaignPda, commitment),
+      contribution: findContributionPda(campaignPda, donor.publicKey),
+      donorProfile: findDonorProfilePda(donor.publicKey),
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([donor])
+    .rpc();
+}
+
+async function markFailed(campaignPda: PublicKey) {
+  const { authority } = await program.account.campaign.fetch(campaignPda);
+  return program.methods
+    .markFailed()
+    .accounts({
+      campaign: campaignPda,
+      globalStats: findGlobalStatsPda(),
+      creatorProfile: findCreatorProfilePda(authority),
+    })
+    .rpc();
+}
+
+async function reclaimMatch(
+  authority: Keypair,
+  campaignPda: PublicKey,
+  vaultLamportsPda: PublicKey
+) {
+  return program.methods
+    .reclaimMatch()
+    .accounts({
+      authority: authority.publicKey,
+      campaign: campaignPda,
+      vaultLamports: vaultLamportsPda,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([authority])
+    .rpc();
+}
+
+const END_ACTION_AUTO_FINALIZE = 0;
+const END_ACTION_ENABLE_REFUNDS = 1;
+const END_ACTION_KEEP_OPEN = 2;
+const END_ACTION_PAUSE = 3;
+const END_ACTION_ALLOW_FINALIZE_ONLY = 4;
+
+async function processDeadline(
+  campaignPda: PublicKey,
+  beneficiary: PublicKey,
+  vaultLamportsPda: PublicKey
+) {
+  return program.methods
+    .processDeadline()
+    .accounts({
+      campaign: campaignPda,
+      beneficiary,
+      authority: provider.wallet.publicKey,
+      vaultLamports: vaultLamportsPda,
+      globalStats: findGlobalStatsPda(),
+      creatorProfile: findCreatorProfilePda(provider.wallet.publicKey),
+      protocolConfig: findProtocolConfigPda(),
+      treasury: await protocolTreasury(),
+      systemProgram: SystemProgram.programId,
+    })
+    .rpc();
+}
+
+async function depositLogs(
+  donor: Keypair,
+  campaignPda: PublicKey,
+  vaultLamportsPda: PublicKey,
+  lamports: number
+): Promise<string[]> {
+  const sig = await program.methods
+    .deposit(new anchor.BN(lamports))
+    .accounts({
+      donor: donor.publicKey,
+      campaign: campaignPda,
+      beneficiary: await campaignBeneficiary(campaignPda),
+      vaultLamports: vaultLamportsPda,
+      contribution: findContributionPda(campaignPda, donor.publicKey),
+      socialAttestation: await socialAttestationFor(campaignPda, donor.publicKey),
+      donorProfile: findDonorProfilePda(donor.publicKey),
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([donor])
+    .rpc({ commitment: "confirmed" });
+  const tx = await provider.connection.getTransaction(sig, {
+    commitment: "confirmed",
+    maxSupportedTransactionVersion: 0,
+  });
+  return tx?.meta?.logMessages ?? [];
+}
+
+async function withdrawBy(
+  beneficiary: Keypair,
+  campaignPda: PublicKey,
+  vaultLamportsPda: PublicKey,
+  lamports: number
+) {
+  return program.methods
+    .withdraw(new anchor.BN(lamports))
+    .accounts({
+      beneficiary: beneficiary.publicKey,
+      campaign: campaignPda,
+      vaultLamports: vaultLamportsPda,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([beneficiary])
+    .rpc();
+}
+
+async function importState(
+  campaignPda: PublicKey,
+  vaultLamportsPda: PublicKey,
+  blob: Buffer,
+  beneficiary: PublicKey
+) {
+  return program.methods
+    .importState(blob)
+    .accounts({
+      authority: provider.wallet.publicKey,
+      campaign: campaignPda,
+      vaultLamports: vaultLamportsPda,
+      beneficiary,
+      protocolConfig: findProtocolConfigPda(),
+      beneficiaryRegistry: findBeneficiaryRegistryPda(beneficiary),
+    })
+    .rpc();
+}
+
+async function finalizeLogs(
+  beneficiary: Keypair,
+  campaignPda: PublicKey,
+  vaultLamportsPda: PublicKey
+): Promise<string[]> {
+  const sig = await program.methods
+    .finalize()
+    .accounts({
+      beneficiary: beneficiary.publicKey,
+      authority: provider.wallet.publicKey,
+      campaign: campaignPda,
+      vaultLamports: vaultLamportsPda,
+      globalStats: findGlobalStatsPda(),
+      creatorProfile: findCreatorProfilePda(provider.wallet.publicKey),
+      protocolConfig: findProtocolConfigPda(),
+      treasury: await protocolTreasury(),
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([beneficiary])
+    .rpc({ commitment: "confirmed" });
+  const tx = await provider.connection.getTransaction(sig, {
+    commitment: "confirmed",
+    maxSupportedTransactionVersion: 0,
+  });
+  return tx?.meta?.logMessages ?? [];
+}
+
+async function donorRemaining(campaignPda: PublicKey, donor: PublicKey): Promise<anchor.BN> {
+  return program.methods
+    .donorRemaining()
+    .accounts({
+      campaign: campaignPda,
+      donor,
+      contribution: findContributionPda(campaignPda, donor),
+    })
+    .view();
+}
+
+const TOKEN_PROGRAM_ID = new PublicKey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+const TOKEN_ACCOUNT_LEN = 165;
+
+function findRewardMintPda(campaignPda: PublicKey): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("reward_mint"), campaignPda.toBuffer()],
+    program.programId
+  );
+  return pda;
+}
+
+async function finalizeAndReward(
+  beneficiary: Keypair,
+  campaignPda: PublicKey,
+  vaultLamportsPda: PublicKey
+) {
+  return program.methods
+    .finalizeAndReward()
+    .accounts({
+      beneficiary: beneficiary.publicKey,
+      authority: provider.wallet.publicKey,
+      campaign: campaignPda,
+      vaultLamports: vaultLamportsPda,
+      rewardMint: findRewardMintPda(campaignPda),
+      globalStats: findGlobalStatsPda(),
+      creatorProfile: findCreatorProfilePda(provider.wallet.publicKey),
+      protocolConfig: findProtocolConfigPda(),
+      treasury: await protocolTreasury(),
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([beneficiary])
+    .rpc();
+}
+
+// Plain SPL token account (InitializeAccount3) without pulling in spl-token
+async function createTokenAccount(mint: PublicKey, owner: PublicKey): Promise<PublicKey> {
+  const account = Keypair.generate();
+  const lamports = await provider.connection.getMinimumBalanceForRentExemption(TOKEN_ACCOUNT_LEN);
+  const initializeAccount = new TransactionInstruction({
+    programId: TOKEN_PROGRAM_ID,
+    keys: [
+      { pubkey: account.publicKey, isSigner: false, isWritable: true },
+      { pubkey: mint, isSigner: false, isWritable: false },
+    ],
+    data: Buffer.concat([Buffer.from([18]), owner.toBuffer()]),
+  });
+  const tx = new Transaction().add(
+    SystemProgram.createAccount({
+      fromPubkey: provider.wallet.publicKey,
+      newAccountPubkey: account.publicKey,
+      lamports,
+      space: TOKEN_ACCOUNT_LEN,
+      programId: TOKEN_PROGRAM_ID,
+    }),
+    initializeAccount
+  );
+  await provider.sendAndConfirm(tx, [account]);
+  return account.publicKey;
+}
+
+async function claimReward(donor: Keypair, campaignPda: PublicKey, destination: PublicKey) {
+  return program.methods
+    .claimReward()
+    .accounts({
+      donor: donor.publicKey,
+      campaign: campaignPda,
+      contribution: findContributionPda(campaignPda, donor.publicKey),
+      rewardMint: findRewardMintPda(campaignPda),
+      destination,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([donor])
+    .rpc();
+}
+
+async function updateRefundPolicy(
+  campaignPda: PublicKey,
+  policy: ReturnType<typeof defaultRefundPolicy>
+) {
+  return program.methods
+    .updateRefundPolicy(policy)
+    .accounts({ authority: provider.wallet.publicKey, campaign: campaignPda })
+    .rpc();
+}
+
+async function updateTarget(campaignPda: PublicKey, target: number) {
+  return program.methods
+    .updateTarget(new anchor.BN(target))
+    .accounts({ authority: provider.wallet.publicKey, campaign: campaignPda })
+    .rpc();
+}
+
+async function distributeOverflow(
+  campaignPda: PublicKey,
+  beneficiary: PublicKey,
+  vaultLamportsPda: PublicKey,
+  donors: PublicKey[]
+) {
+  return program.methods
+    .distributeOverflow(donors.length)
+    .accounts({
+      campaign: campaignPda,
+      beneficiary,
+      vaultLamports: vaultLamportsPda,
+      systemProgram: SystemProgram.programId,
+    })
+    .remainingAccounts(
+      donors.flatMap((donor) => [
+        { pubkey: findContributionPda(campaignPda, donor), isWritable: true, isSigner: false },
+        { pubkey: donor, isWritable: true, isSigner: false },
+      ])
+    )
+    .rpc();
+}
+
+async function unlockContent(donor: Keypair, campaignPda: PublicKey): Promise<number[]> {
+  return program.methods
+    .unlockContent()
+    .accounts({
+      donor: donor.publicKey,
+      campaign: campaignPda,
+      contribution: findContributionPda(campaignPda, donor.publicKey),
+    })
+    .signers([donor])
+    .view();
+}
+
+async function updateFee(campaignPda: PublicKey, newFeeBps: number) {
+  return program.methods
+    .updateFee(newFeeBps)
+    .accounts({ authority: provider.wallet.publicKey, campaign: campaignPda })
+    .rpc();
+}
+
+async function postComment(donor: Keypair, campaignPda: PublicKey, commentHash: Buffer) {
+  return program.methods
+    .postComment([...commentHash])
+    .accounts({
+      donor: donor.publicKey,
+      campaign: campaignPda,
+      contribution: findContributionPda(campaignPda, donor.publicKey),
+    })
+    .signers([donor])
+    .rpc();
+}
+
+async function raiseDispute(donor: Keypair, campaignPda: PublicKey) {
+  return program.methods
+    .raiseDispute()
+    .accounts({
+      donor: donor.publicKey,
+      campaign: campaignPda,
+      contribution: findContributionPda(campaignPda, donor.publicKey),
+    })
+    .signers([donor])
+    .rpc();
+}
+
+async function resolveDispute(arbiter: Keypair, campaignPda: PublicKey, favorDonors: boolean) {
+  const { authority } = await program.account.campaign.fetch(campaignPda);
+  return program.methods
+    .resolveDispute(favorDonors)
+    .accounts({
+      arbiter: arbiter.publicKey,
+      campaign: campaignPda,
+      globalStats: findGlobalStatsPda(),
+      creatorProfile: findCreatorProfilePda(authority),
+    })
+    .signers([arbiter])
+    .rpc();
+}
+
+async function creatorReputation(campaignPda: PublicKey): Promise<number> {
+  const { authority } = await program.account.campaign.fetch(campaignPda);
+  return program.methods
+    .creatorReputation()
+    .accounts({ campaign: campaignPda, creatorProfile: findCreatorProfilePda(authority) })
+    .view();
+}
+
+// Mirrors the on-chain score: success ratio 70%, volume towards 100 SOL 30%
+function expectedReputation(profile: {
+  campaignsSucceeded: number;
+  campaignsFailed: number;
+  totalRaised: anchor.BN;
+}): number {
+  const settled = profile.campaignsSucceeded + profile.campaignsFailed;
+  if (settled === 0) {
+    return 0;
+  }
+  const success = Math.floor((profile.campaignsSucceeded * 100) / settled);
+  const volume = Math.min(
+    100,
+    profile.totalRaised.muln(100).div(new anchor.BN(100_000_000_000)).toNumber()
+  );
+  return Math.floor((70 * success + 30 * volume) / 100);
+}
+
+function findVoucherEscrowPda(donor: PublicKey): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("voucher_escrow"), donor.toBuffer()],
+    program.programId
+  );
+  return pda;
+}
+
+function findVoucherNoncePda(campaignPda: PublicKey, donor: PublicKey, nonce: anchor.BN): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("voucher_nonce"), campaignPda.toBuffer(), donor.toBuffer(), u64leBN(nonce)],
+    program.programId
+  );
+  return pda;
+}
+
+async function fundVoucherEscrow(donor: Keypair, lamports: number) {
+  return program.methods
+    .fundVoucherEscrow(new anchor.BN(lamports))
+    .accounts({
+      donor: donor.publicKey,
+      escrow: findVoucherEscrowPda(donor.publicKey),
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([donor])
+    .rpc();
+}
+
+// The provider wallet relays a voucher `signer` signed for `amount` and `nonce`;
+// `amount` alone can differ from the signed amount to simulate tampering
+async function depositVoucher(
+  signer: Keypair,
+  donor: PublicKey,
+  campaignPda: PublicKey,
+  vaultLamportsPda: PublicKey,
+  amount: number,
+  nonce: number,
+  signedAmount = amount
+) {
+  const message = Buffer.concat([
+    campaignPda.toBuffer(),
+    donor.toBuffer(),
+    u64leBN(new anchor.BN(signedAmount)),
+    u64leBN(new anchor.BN(nonce)),
+  ]);
+  const verify = Ed25519Program.createInstructionWithPrivateKey({
+    privateKey: signer.secretKey,
+    message,
+  });
+  return program.methods
+    .depositVoucher(new anchor.BN(amount), new anchor.BN(nonce))
+    .accounts({
+      relayer: provider.wallet.publicKey,
+      donor,
+      campaign: campaignPda,
+      vaultLamports: vaultLamportsPda,
+      escrow: findVoucherEscrowPda(donor),
+      voucherNonce: findVoucherNoncePda(campaignPda, donor, new anchor.BN(nonce)),
+      contribution: findContributionPda(campaignPda, donor),
+      donorProfile: findDonorProfilePda(donor),
+      instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+      systemProgram: SystemProgram.programId,
+    })
+    .preInstructions([verify])
+    .rpc();
+}
+
+async function setMaxActivePerCreator(max: number) {
+  return program.methods
+    .setMaxActivePerCreator(max)
+    .accounts({
+      admin: provider.wallet.publicKey,
+      protocolConfig: await ensureProtocolConfig(),
+    })
+    .rpc();
+}
+
+async function extendDeadline(authority: Keypair, campaignPda: PublicKey, newDeadline: number) {
+  return program.methods
+    .extendDeadline(new anchor.BN(newDeadline))
+    .accounts({ authority: authority.publicKey, campaign: campaignPda })
+    .signers([authority])
+    .rpc();
+}
+
+async function issueSocialAttestation(issuer: Keypair, subject: PublicKey, expiresAt: number) {
+  return program.methods
+    .issueSocialAttestation(subject, new anchor.BN(expiresAt))
+    .accounts({
+      issuer: issuer.publicKey,
+      attestation: findSocialAttestationPda(issuer.publicKey, subject),
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([issuer])
+    .rpc();
+}
+
+async function finalizeAndCleanup(
+  beneficiary: Keypair,
+  campaignPda: PublicKey,
+  vaultLamportsPda: PublicKey,
+  contributions: PublicKey[]
+) {
+  return program.methods
+    .finalizeAndCleanup(contributions.length)
+    .accounts({
+      beneficiary: beneficiary.publicKey,
+      authority: provider.wallet.publicKey,
+      campaign: campaignPda,
+      vaultLamports: vaultLamportsPda,
+      globalStats: findGlobalStatsPda(),
+      creatorProfile: findCreatorProfilePda(provider.wallet.publicKey),
+      protocolConfig: findProtocolConfigPda(),
+      treasury: await protocolTreasury(),
+      systemProgram: SystemProgram.programId,
+    })
+    .remainingAccounts(
+      contributions.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false }))
+    )
+    .signers([beneficiary])
+    .rpc();
+}
+
+function findCreatorBondPda(campaignPda: PublicKey): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("creator_bond"), campaignPda.toBuffer()],
+    program.programId
+  );
+  return pda;
+}
+
+async function claimBond(campaignPda: PublicKey) {
+  return program.methods
+    .claimBond()
+    .accounts({
+      authority: provider.wallet.publicKey,
+      campaign: campaignPda,
+      creatorBond: findCreatorBondPda(campaignPda),
+      systemProgram: SystemProgram.programId,
+    })
+    .rpc();
+}
+
+async function slashBond(slasher: Keypair, campaignPda: PublicKey, donors: PublicKey[]) {
+  return program.methods
+    .slashBond(donors.length)
+    .accounts({
+      slasher: slasher.publicKey,
+      campaign: campaignPda,
+      creatorBond: findCreatorBondPda(campaignPda),
+      protocolConfig: findProtocolConfigPda(),
+      systemProgram: SystemProgram.programId,
+    })
+    .remainingAccounts(
+      donors.flatMap((donor) => [
+        { pubkey: findContributionPda(campaignPda, donor), isWritable: true, isSigner: false },
+        { pubkey: donor, isWritable: true, isSigner: false },
+      ])
+    )
+    .signers([slasher])
+    .rpc();
+}
+
+async function donorReputation(donor: PublicKey): Promise<anchor.BN> {
+  return program.methods
+    .donorReputation()
+    .accounts({ donor, donorProfile: findDonorProfilePda(donor) })
+    .view();
+}
+
+async function crankFinalize(cranker: Keypair, campaignPda: PublicKey) {
+  const { beneficiary, authority } = await program.account.campaign.fetch(campaignPda);
+  return program.methods
+    .crankFinalize()
+    .accounts({
+      cranker: cranker.publicKey,
+      campaign: campaignPda,
+      beneficiary,
+      authority,
+      vaultLamports: findVaultLamportsPda(campaignPda),
+      globalStats: findGlobalStatsPda(),
+      creatorProfile: findCreatorProfilePda(authority),
+      protocolConfig: findProtocolConfigPda(),
+      treasury: await protocolTreasury(),
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([cranker])
+    .rpc();
+}
+
+describe("test_project", () => {
+  const creator = (provider.wallet as anchor.Wallet).payer;
+
+  it("initialize creates campaign + vault lamports PDA with expected state", async () => {
+    const beneficiary = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+
+    const targetLamports = Math.floor(0.05 * LAMPORTS_PER_SOL);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports,
+    });
+
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), "0");
+    assert.equal(campaign.target.toString(), targetLamports.toString());
+    assert.equal(campaign.authority.toBase58(), creator.publicKey.toBase58());
+    assert.equal(
+      campaign.beneficiary.toBase58(),
+      beneficiary.publicKey.toBase58()
+    );
+    assert.equal(campaign.isFinalized, false);
+
+    const vaultLamportsInfo = await provider.connection.getAccountInfo(
+      vaultLamportsPda
+    );
+    assert.isNotNull(vaultLamportsInfo, "vault lamports should exist");
+    assert.equal(
+      vaultLamportsInfo?.owner.toBase58(),
+      SystemProgram.programId.toBase58()
+    );
+  });
+
+  it("deposit increases campaign.funds and vault lamports", async () => {
+    const beneficiary = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+
+    const targetLamports = Math.floor(0.05 * LAMPORTS_PER_SOL);
+    const depositLamports = Math.floor(0.01 * LAMPORTS_PER_SOL);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports,
+    });
+
+    const donorBalanceBefore = await provider.connection.getBalance(
+      creator.publicKey
+    );
+    const vaultBalanceBefore = await provider.connection.getBalance(
+      vaultLamportsPda
+    );
+
+    await program.methods
+      .deposit(new anchor.BN(depositLamports))
+      .accounts({
+        donor: creator.publicKey,
+        campaign: campaignPda,
+        beneficiary: await campaignBeneficiary(campaignPda),
+        vaultLamports: vaultLamportsPda,
+        contribution: findContributionPda(campaignPda, creator.publicKey),
+        socialAttestation: await socialAttestationFor(campaignPda, creator.publicKey),
+        donorProfile: findDonorProfilePda(creator.publicKey),
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), depositLamports.toString());
+
+    const vaultBalanceAfter = await provider.connection.getBalance(
+      vaultLamportsPda
+    );
+    assert.equal(vaultBalanceAfter - vaultBalanceBefore, depositLamports);
+
+    const donorBalanceAfter = await provider.connection.getBalance(
+      creator.publicKey
+    );
+    assert.ok(
+      donorBalanceAfter <= donorBalanceBefore - depositLamports,
+      "donor should pay at least the deposit amount (plus fees)"
+    );
+  });
+
+  it("finalize transfers funds to beneficiary and closes vault", async () => {
+    const beneficiary = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+
+    const targetLamports = Math.floor(0.05 * LAMPORTS_PER_SOL);
+    const depositLamports = Math.floor(0.02 * LAMPORTS_PER_SOL);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports,
+    });
+
+    await program.methods
+      .deposit(new anchor.BN(depositLamports))
+      .accounts({
+        donor: creator.publicKey,
+        campaign: campaignPda,
+        beneficiary: await campaignBeneficiary(campaignPda),
+        vaultLamports: vaultLamportsPda,
+        contribution: findContributionPda(campaignPda, creator.publicKey),
+        socialAttestation: await socialAttestationFor(campaignPda, creator.publicKey),
+        donorProfile: findDonorProfilePda(creator.publicKey),
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const beneficiaryBalanceBefore = await provider.connection.getBalance(
+      beneficiary.publicKey
+    );
+
+    await program.methods
+      .finalize()
+      .accounts({
+        beneficiary: beneficiary.publicKey,
+        authority: creator.publicKey,
+        campaign: campaignPda,
+        vaultLamports: vaultLamportsPda,
+        globalStats: findGlobalStatsPda(),
+        creatorProfile: findCreatorProfilePda(creator.publicKey),
+        protocolConfig: findProtocolConfigPda(),
+        treasury: await protocolTreasury(),
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([beneficiary])
+      .rpc();
+
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.isFinalized, true);
+    assert.equal(campaign.funds.toString(), "0");
+
+    const beneficiaryBalanceAfter = await provider.connection.getBalance(
+      beneficiary.publicKey
+    );
+    const delta = beneficiaryBalanceAfter - beneficiaryBalanceBefore;
+    const feeBuffer = 20_000;
+    assert.ok(
+      delta >= depositLamports - feeBuffer,
+      "beneficiary balance should increase by deposited funds minus fees"
+    );
+
+    const vaultLamportsAfter = await provider.connection.getBalance(
+      vaultLamportsPda
+    );
+    assert.equal(vaultLamportsAfter, 0);
+  });
+
+  it("finalize rejects unauthorized caller", async () => {
+    const beneficiary = Keypair.generate();
+    const unauthorized = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+    await airdrop(unauthorized.publicKey);
+
+    const targetLamports = Math.floor(0.05 * LAMPORTS_PER_SOL);
+    const depositLamports = Math.floor(0.01 * LAMPORTS_PER_SOL);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports,
+    });
+
+    await program.methods
+      .deposit(new anchor.BN(depositLamports))
+      .accounts({
+        donor: creator.publicKey,
+        campaign: campaignPda,
+        beneficiary: await campaignBeneficiary(campaignPda),
+        vaultLamports: vaultLamportsPda,
+        contribution: findContributionPda(campaignPda, creator.publicKey),
+        socialAttestation: await socialAttestationFor(campaignPda, creator.publicKey),
+        donorProfile: findDonorProfilePda(creator.publicKey),
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    await expectError(
+      program.methods
+        .finalize()
+        .accounts({
+          beneficiary: unauthorized.publicKey,
+          authority: creator.publicKey,
+          campaign: campaignPda,
+          vaultLamports: vaultLamportsPda,
+          globalStats: findGlobalStatsPda(),
+          creatorProfile: findCreatorProfilePda(creator.publicKey),
+          protocolConfig: findProtocolConfigPda(),
+          treasury: await protocolTreasury(),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([unauthorized])
+        .rpc(),
+      /Unauthorized|has one|ConstraintHasOne/i
+    );
+  });
+
+  it("double finalize is rejected", async () => {
+    const beneficiary = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+
+    const targetLamports = Math.floor(0.05 * LAMPORTS_PER_SOL);
+    const depositLamports = Math.floor(0.01 * LAMPORTS_PER_SOL);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports,
+    });
+
+    await program.methods
+      .deposit(new anchor.BN(depositLamports))
+      .accounts({
+        donor: creator.publicKey,
+        campaign: campaignPda,
+        beneficiary: await campaignBeneficiary(campaignPda),
+        vaultLamports: vaultLamportsPda,
+        contribution: findContributionPda(campaignPda, creator.publicKey),
+        socialAttestation: await socialAttestationFor(campaignPda, creator.publicKey),
+        donorProfile: findDonorProfilePda(creator.publicKey),
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    await program.methods
+      .finalize()
+      .accounts({
+        beneficiary: beneficiary.publicKey,
+        authority: creator.publicKey,
+        campaign: campaignPda,
+        vaultLamports: vaultLamportsPda,
+        globalStats: findGlobalStatsPda(),
+        creatorProfile: findCreatorProfilePda(creator.publicKey),
+        protocolConfig: findProtocolConfigPda(),
+        treasury: await protocolTreasury(),
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([beneficiary])
+      .rpc();
+
+    await expectError(
+      program.methods
+        .finalize()
+        .accounts({
+          beneficiary: beneficiary.publicKey,
+          authority: creator.publicKey,
+          campaign: campaignPda,
+          vaultLamports: vaultLamportsPda,
+          globalStats: findGlobalStatsPda(),
+          creatorProfile: findCreatorProfilePda(creator.publicKey),
+          protocolConfig: findProtocolConfigPda(),
+          treasury: await protocolTreasury(),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([beneficiary])
+        .rpc(),
+      /CampaignFinalized|Constraint.*finalized|AccountNotInitialized|AccountNotFound|closed/i
+    );
+  });
+
+  it("deposit of zero lamports is rejected", async () => {
+    const beneficiary = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+
+    const targetLamports = Math.floor(0.05 * LAMPORTS_PER_SOL);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports,
+    });
+
+    await expectError(
+      program.methods
+        .deposit(new anchor.BN(0))
+        .accounts({
+        donor: creator.publicKey,
+        campaign: campaignPda,
+        beneficiary: await campaignBeneficiary(campaignPda),
+        vaultLamports: vaultLamportsPda,
+        contribution: findContributionPda(campaignPda, creator.publicKey),
+        socialAttestation: await socialAttestationFor(campaignPda, creator.publicKey),
+        donorProfile: findDonorProfilePda(creator.publicKey),
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc(),
+      /InvalidAmount/i
+    );
+  });
+
+  it("deposit after finalize is rejected", async () => {
+    const beneficiary = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+
+    const targetLamports = Math.floor(0.05 * LAMPORTS_PER_SOL);
+    const depositLamports = Math.floor(0.01 * LAMPORTS_PER_SOL);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports,
+    });
+
+    await program.methods
+      .deposit(new anchor.BN(depositLamports))
+      .accounts({
+        donor: creator.publicKey,
+        campaign: campaignPda,
+        beneficiary: await campaignBeneficiary(campaignPda),
+        vaultLamports: vaultLamportsPda,
+        contribution: findContributionPda(campaignPda, creator.publicKey),
+        socialAttestation: await socialAttestationFor(campaignPda, creator.publicKey),
+        donorProfile: findDonorProfilePda(creator.publicKey),
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    await program.methods
+      .finalize()
+      .accounts({
+        beneficiary: beneficiary.publicKey,
+        authority: creator.publicKey,
+        campaign: campaignPda,
+        vaultLamports: vaultLamportsPda,
+        globalStats: findGlobalStatsPda(),
+        creatorProfile: findCreatorProfilePda(creator.publicKey),
+        protocolConfig: findProtocolConfigPda(),
+        treasury: await protocolTreasury(),
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([beneficiary])
+      .rpc();
+
+    await expectError(
+      program.methods
+        .deposit(new anchor.BN(1))
+        .accounts({
+          donor: creator.publicKey,
+          campaign: campaignPda,
+          beneficiary: await campaignBeneficiary(campaignPda),
+          vaultLamports: vaultLamportsPda,
+          contribution: findContributionPda(campaignPda, creator.publicKey),
+          socialAttestation: await socialAttestationFor(campaignPda, creator.publicKey),
+          donorProfile: findDonorProfilePda(creator.publicKey),
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc(),
+      /CampaignFinalized|AccountNotInitialized|AccountNotFound|closed/i
+    );
+  });
+
+  it("mismatched vault lamports seed is rejected", async () => {
+    const beneficiaryA = Keypair.generate();
+    const beneficiaryB = Keypair.generate();
+    await airdrop(beneficiaryA.publicKey);
+    await airdrop(beneficiaryB.publicKey);
+
+    const targetLamports = Math.floor(0.05 * LAMPORTS_PER_SOL);
+
+    const {
+      campaignPda: campaignA,
+      vaultLamportsPda: vaultLamportsA,
+    } = await initCampaign({
+      creator,
+      beneficiary: beneficiaryA.publicKey,
+      targetLamports,
+    });
+
+    const {
+      campaignPda: campaignB,
+      vaultLamportsPda: vaultLamportsB,
+    } = await initCampaign({
+      creator,
+      beneficiary: beneficiaryB.publicKey,
+      targetLamports,
+    });
+
+    await expectError(
+      program.methods
+        .deposit(new anchor.BN(1_000))
+        .accounts({
+          donor: creator.publicKey,
+          campaign: campaignA,
+          beneficiary: await campaignBeneficiary(campaignA),
+          vaultLamports: vaultLamportsB,
+          contribution: findContributionPda(campaignA, creator.publicKey),
+          socialAttestation: await socialAttestationFor(campaignA, creator.publicKey),
+          donorProfile: findDonorProfilePda(creator.publicKey),
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc(),
+      /ConstraintSeeds|seeds constraint/i
+    );
+  });
+
+  it("deposit is matched along the diminishing tier curve", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const tier = Math.floor(0.01 * LAMPORTS_PER_SOL);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: Math.floor(0.1 * LAMPORTS_PER_SOL),
+      config: {
+        matchTiers: [
+          { amount: new anchor.BN(tier), rateBps: 10_000 },
+          { amount: new anchor.BN(tier), rateBps: 5_000 },
+          { amount: new anchor.BN(0), rateBps: 0 },
+        ],
+      },
+    });
+    await fundMatch(creator, campaignPda, vaultLamportsPda, 2 * tier);
+
+    // 1.5 tiers deposited: first tier matched 1:1, half of the second at 50%
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 1.5 * tier);
+    let campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), (1.5 * tier + 1.25 * tier).toString());
+    assert.equal(campaign.matchPool.toString(), (0.75 * tier).toString());
+
+    // Remaining half of the second tier at 50%, nothing beyond the curve
+    await depositFrom(donor, campaignPda, vaultLamportsPda, tier);
+    campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), (2.5 * tier + 1.5 * tier).toString());
+    assert.equal(campaign.matchPool.toString(), (0.5 * tier).toString());
+
+    const contribution = await program.account.contribution.fetch(
+      findContributionPda(campaignPda, donor.publicKey)
+    );
+    assert.equal(contribution.amount.toString(), (2.5 * tier).toString());
+    assert.equal(contribution.matched.toString(), (1.5 * tier).toString());
+  });
+
+  it("match is clamped to the available match pool", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const tier = Math.floor(0.01 * LAMPORTS_PER_SOL);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: Math.floor(0.1 * LAMPORTS_PER_SOL),
+      config: {
+        matchTiers: [
+          { amount: new anchor.BN(tier), rateBps: 10_000 },
+          { amount: new anchor.BN(0), rateBps: 0 },
+          { amount: new anchor.BN(0), rateBps: 0 },
+        ],
+      },
+    });
+    await fundMatch(creator, campaignPda, vaultLamportsPda, tier / 4);
+
+    await depositFrom(donor, campaignPda, vaultLamportsPda, tier);
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), (1.25 * tier).toString());
+    assert.equal(campaign.matchPool.toString(), "0");
+  });
+
+
+  it("contributed_before reports early backers only", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    const stranger = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: Math.floor(0.05 * LAMPORTS_PER_SOL),
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 1_000_000);
+
+    const contribution = await program.account.contribution.fetch(
+      findContributionPda(campaignPda, donor.publicKey)
+    );
+    const firstAt = contribution.firstContributedAt.toNumber();
+
+    const contributedBefore = (who: PublicKey, cutoff: number) =>
+      program.methods
+        .contributedBefore(new anchor.BN(cutoff))
+        .accounts({
+          campaign: campaignPda,
+          donor: who,
+          contribution: findContributionPda(campaignPda, who),
+        })
+        .view();
+
+    assert.isTrue(await contributedBefore(donor.publicKey, firstAt));
+    assert.isFalse(await contributedBefore(donor.publicKey, firstAt - 1));
+    assert.isFalse(await contributedBefore(stranger.publicKey, firstAt + 60));
+  });
+
+
+  it("assert_invariants passes for a healthy campaign", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: Math.floor(0.05 * LAMPORTS_PER_SOL),
+    });
+    await fundMatch(creator, campaignPda, vaultLamportsPda, 500_000);
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 1_000_000);
+
+    const sig = await program.methods
+      .assertInvariants()
+      .accounts({ campaign: campaignPda, vaultLamports: vaultLamportsPda })
+      .remainingAccounts([
+        {
+          pubkey: findContributionPda(campaignPda, donor.publicKey),
+          isSigner: false,
+          isWritable: false,
+        },
+      ])
+      .rpc({ commitment: "confirmed" });
+
+    const tx = await provider.connection.getTransaction(sig, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const parser = new anchor.EventParser(program.programId, program.coder);
+    const events = [...parser.parseLogs(tx?.meta?.logMessages ?? [])];
+    assert.equal(events.length, 1);
+    assert.equal(events[0].name, "invariantsOk");
+    assert.equal(events[0].data.receiptsTotal.toString(), "1000000");
+  });
+
+  it("assert_invariants rejects receipts from another campaign", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const target = Math.floor(0.05 * LAMPORTS_PER_SOL);
+    const a = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: target,
+    });
+    const b = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: target,
+    });
+    await depositFrom(donor, b.campaignPda, b.vaultLamportsPda, 1_000_000);
+
+    await expectError(
+      program.methods
+        .assertInvariants()
+        .accounts({ campaign: a.campaignPda, vaultLamports: a.vaultLamportsPda })
+        .remainingAccounts([
+          {
+            pubkey: findContributionPda(b.campaignPda, donor.publicKey),
+            isSigner: false,
+            isWritable: false,
+          },
+        ])
+        .rpc(),
+      /InvalidReceipt/i
+    );
+  });
+
+
+  it("archive keeps a compact record and closes the finalized campaign", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda, campaignId } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: Math.floor(0.05 * LAMPORTS_PER_SOL),
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 1_000_000);
+
+    const archivedPda = findArchivedCampaignPda(campaignPda);
+    const archive = () =>
+      program.methods
+        .archive()
+        .accounts({
+          authority: creator.publicKey,
+          campaign: campaignPda,
+          archivedCampaign: archivedPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+    await expectError(archive(), /CampaignNotFinalized/i);
+
+    await finalizeBy(beneficiary, creator.publicKey, campaignPda, vaultLamportsPda);
+    await expectError(archive(), /CampaignNotCleaned/i);
+    await finalizeAndCleanup(beneficiary, campaignPda, vaultLamportsPda, [
+      findContributionPda(campaignPda, donor.publicKey),
+    ]);
+    const finalized = await program.account.campaign.fetch(campaignPda);
+    await archive();
+
+    const archived = await program.account.archivedCampaign.fetch(archivedPda);
+    assert.equal(archived.campaign.toBase58(), campaignPda.toBase58());
+    assert.equal(archived.campaignId.toString(), campaignId.toString());
+    assert.equal(archived.finalFunds.toString(), "1000000");
+    assert.equal(archived.donorCount, 1);
+    assert.equal(
+      archived.createdAt.toString(),
+      finalized.createdAt.toString()
+    );
+    assert.equal(
+      archived.finalizedAt.toString(),
+      finalized.finalizedAt.toString()
+    );
+
+    // The archived key cannot be initialized again
+    await expectError(
+      program.methods
+        .initialize(campaignId, new anchor.BN(1_000_000), defaultConfig())
+        .accounts({
+          creator: creator.publicKey,
+          beneficiary: beneficiary.publicKey,
+          campaign: campaignPda,
+          vaultLamports: vaultLamportsPda,
+          creatorBond: findCreatorBondPda(campaignPda),
+          globalStats: findGlobalStatsPda(),
+          creatorProfile: findCreatorProfilePda(creator.publicKey),
+          protocolConfig: findProtocolConfigPda(),
+          beneficiaryRegistry: findBeneficiaryRegistryPda(beneficiary.publicKey),
+          archivedCampaign: archivedPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc(),
+      /CampaignArchived/i
+    );
+
+    assert.isNull(await provider.connection.getAccountInfo(campaignPda));
+  });
+
+
+  it("deposit_cpi rejects top-level invocation", async () => {
+    const beneficiary = Keypair.generate();
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: Math.floor(0.05 * LAMPORTS_PER_SOL),
+    });
+
+    await expectError(
+      program.methods
+        .depositCpi(new anchor.BN(1_000))
+        .accounts({
+          donor: creator.publicKey,
+          payer: creator.publicKey,
+          campaign: campaignPda,
+          vaultLamports: vaultLamportsPda,
+          contribution: findContributionPda(campaignPda, creator.publicKey),
+          donorProfile: findDonorProfilePda(creator.publicKey),
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc(),
+      /NotInvokedViaCpi/i
+    );
+  });
+
+
+  it("strict target finalizes only at exactly the target", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+    await airdrop(donor.publicKey);
+
+    const targetLamports = 1_000_000;
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports,
+      config: { strictTarget: true },
+    });
+
+    await depositFrom(donor, campaignPda, vaultLamportsPda, targetLamports - 1);
+    await expectError(
+      finalizeBy(beneficiary, creator.publicKey, campaignPda, vaultLamportsPda),
+      /TargetNotReached/i
+    );
+
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 1);
+    await finalizeBy(beneficiary, creator.publicKey, campaignPda, vaultLamportsPda);
+
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.isFinalized, true);
+    assert.equal(campaign.finalFunds.toString(), targetLamports.toString());
+  });
+
+
+  it("refund keeps the beneficiary fee in the campaign", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: Math.floor(0.05 * LAMPORTS_PER_SOL),
+      config: {
+        refundPolicy: { ...defaultRefundPolicy(), refundsEnabled: true, refundPenaltyBps: 1_000 },
+      },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 1_000_000);
+
+    const vaultBefore = await provider.connection.getBalance(vaultLamportsPda);
+    await refundFrom(donor, campaignPda, vaultLamportsPda);
+    const vaultAfter = await provider.connection.getBalance(vaultLamportsPda);
+
+    assert.equal(vaultBefore - vaultAfter, 900_000);
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), "100000");
+    assert.equal(campaign.donorCount, 0);
+
+    const contribution = await program.account.contribution.fetch(
+      findContributionPda(campaignPda, donor.publicKey)
+    );
+    assert.equal(contribution.amount.toString(), "0");
+
+    await expectError(
+      refundFrom(donor, campaignPda, vaultLamportsPda),
+      /NothingToRefund/i
+    );
+  });
+
+  it("refund is rejected unless enabled", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: Math.floor(0.05 * LAMPORTS_PER_SOL),
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 1_000_000);
+
+    await expectError(
+      refundFrom(donor, campaignPda, vaultLamportsPda),
+      /RefundsDisabled/i
+    );
+  });
+
+  it("initialize rejects a refund fee above 100%", async () => {
+    const beneficiary = Keypair.generate();
+    await expectError(
+      initCampaign({
+        creator,
+        beneficiary: beneficiary.publicKey,
+        targetLamports: 1_000_000,
+        config: {
+          refundPolicy: { ...defaultRefundPolicy(), refundsEnabled: true, refundPenaltyBps: 10_001 },
+        },
+      }),
+      /InvalidFee/i
+    );
+  });
+
+
+  it("relayer deposits escrowed lamports for the donor", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    const relayer = Keypair.generate();
+    const otherRelayer = Keypair.generate();
+    await airdrop(donor.publicKey);
+    await airdrop(relayer.publicKey);
+    await airdrop(otherRelayer.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: Math.floor(0.05 * LAMPORTS_PER_SOL),
+    });
+    const authorizationPda = findDepositAuthorizationPda(
+      campaignPda,
+      donor.publicKey
+    );
+
+    await program.methods
+      .authorizeDeposit(new anchor.BN(1_000_000), relayer.publicKey)
+      .accounts({
+        donor: donor.publicKey,
+        campaign: campaignPda,
+        authorization: authorizationPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([donor])
+      .rpc();
+
+    const depositDelegated = (by: Keypair, lamports: number) =>
+      program.methods
+        .depositDelegated(new anchor.BN(lamports))
+        .accounts({
+          relayer: by.publicKey,
+          donor: donor.publicKey,
+          campaign: campaignPda,
+          vaultLamports: vaultLamportsPda,
+          authorization: authorizationPda,
+          contribution: findContributionPda(campaignPda, donor.publicKey),
+          donorProfile: findDonorProfilePda(donor.publicKey),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([by])
+        .rpc();
+
+    await expectError(
+      depositDelegated(otherRelayer, 1_000_000),
+      /AuthorizationMismatch/i
+    );
+    await expectError(
+      depositDelegated(relayer, 999_999),
+      /AuthorizationMismatch/i
+    );
+
+    await depositDelegated(relayer, 1_000_000);
+
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), "1000000");
+    const contribution = await program.account.contribution.fetch(
+      findContributionPda(campaignPda, donor.publicKey)
+    );
+    assert.equal(contribution.donor.toBase58(), donor.publicKey.toBase58());
+    assert.equal(contribution.amount.toString(), "1000000");
+    assert.isNull(await provider.connection.getAccountInfo(authorizationPda));
+  });
+
+  it("donor can revoke a deposit authorization", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const { campaignPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: Math.floor(0.05 * LAMPORTS_PER_SOL),
+    });
+    const authorizationPda = findDepositAuthorizationPda(
+      campaignPda,
+      donor.publicKey
+    );
+
+    await program.methods
+      .authorizeDeposit(new anchor.BN(1_000_000), creator.publicKey)
+      .accounts({
+        donor: donor.publicKey,
+        campaign: campaignPda,
+        authorization: authorizationPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([donor])
+      .rpc();
+
+    await program.methods
+      .revokeDepositAuthorization()
+      .accounts({ donor: donor.publicKey, authorization: authorizationPda })
+      .signers([donor])
+      .rpc();
+
+    assert.isNull(await provider.connection.getAccountInfo(authorizationPda));
+  });
+
+
+  it("global stats track active, finalized and cancelled campaigns", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+    await airdrop(donor.publicKey);
+
+    const target = Math.floor(0.05 * LAMPORTS_PER_SOL);
+    const a = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: target,
+    });
+    const b = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: target,
+    });
+    const before = await fetchGlobalStats();
+
+    await depositFrom(donor, a.campaignPda, a.vaultLamportsPda, 1_000_000);
+    await finalizeBy(beneficiary, creator.publicKey, a.campaignPda, a.vaultLamportsPda);
+    await cancelCampaign(creator, b.campaignPda);
+
+    const after = await fetchGlobalStats();
+    assert.equal(after.totalCampaigns.toNumber(), before.totalCampaigns.toNumber());
+    assert.equal(
+      after.activeCampaigns.toNumber(),
+      before.activeCampaigns.toNumber() - 2
+    );
+    assert.equal(
+      after.finalizedCampaigns.toNumber(),
+      before.finalizedCampaigns.toNumber() + 1
+    );
+    assert.equal(
+      after.failedCampaigns.toNumber(),
+      before.failedCampaigns.toNumber() + 1
+    );
+  });
+
+  it("cancelled campaign stops deposits and refunds donors in full", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: Math.floor(0.05 * LAMPORTS_PER_SOL),
+      config: { refundPolicy: { ...defaultRefundPolicy(), refundPenaltyBps: 1_000 } },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 1_000_000);
+    await cancelCampaign(creator, campaignPda);
+
+    await expectError(
+      depositFrom(donor, campaignPda, vaultLamportsPda, 1_000),
+      /CampaignCancelled/i
+    );
+    await expectError(cancelCampaign(creator, campaignPda), /CampaignCancelled/i);
+
+    const vaultBefore = await provider.connection.getBalance(vaultLamportsPda);
+    await refundFrom(donor, campaignPda, vaultLamportsPda);
+    const vaultAfter = await provider.connection.getBalance(vaultLamportsPda);
+    assert.equal(vaultBefore - vaultAfter, 1_000_000);
+  });
+
+
+  it("emergency_reclaim recovers lamports stranded in a closed campaign's vault", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    const recovery = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+    await airdrop(donor.publicKey);
+    const protocolConfig = await ensureProtocolConfig();
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: Math.floor(0.05 * LAMPORTS_PER_SOL),
+    });
+
+    const reclaim = () =>
+      program.methods
+        .emergencyReclaim()
+        .accounts({
+          admin: provider.wallet.publicKey,
+          protocolConfig,
+          campaign: campaignPda,
+          vaultLamports: vaultLamportsPda,
+          recovery: recovery.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 1_000_000);
+    await expectError(reclaim(), /CampaignStillOpen/i);
+
+    await finalizeAndCleanup(beneficiary, campaignPda, vaultLamportsPda, [
+      findContributionPda(campaignPda, donor.publicKey),
+    ]);
+    await program.methods
+      .archive()
+      .accounts({
+        authority: creator.publicKey,
+        campaign: campaignPda,
+        archivedCampaign: findArchivedCampaignPda(campaignPda),
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    // Lamports sent to the vault after the campaign is gone
+    const stranded = LAMPORTS_PER_SOL / 100;
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: donor.publicKey,
+          toPubkey: vaultLamportsPda,
+          lamports: stranded,
+        })
+      ),
+      [donor]
+    );
+
+    await reclaim();
+    assert.equal(await provider.connection.getBalance(recovery.publicKey), stranded);
+    assert.equal(await provider.connection.getBalance(vaultLamportsPda), 0);
+  });
+
+
+  it("large deposits are escrowed until confirmed", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: Math.floor(0.05 * LAMPORTS_PER_SOL),
+      config: { largeDepositThreshold: new anchor.BN(1_000_000) },
+    });
+
+    // At the threshold deposits land instantly
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 1_000_000);
+    await expectError(
+      depositFrom(donor, campaignPda, vaultLamportsPda, 2_000_000),
+      /DepositRequiresConfirmation/i
+    );
+
+    const pendingPda = findPendingDepositPda(campaignPda, donor.publicKey);
+    await program.methods
+      .requestLargeDeposit(new anchor.BN(2_000_000))
+      .accounts({
+        donor: donor.publicKey,
+        campaign: campaignPda,
+        pendingDeposit: pendingPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([donor])
+      .rpc({ commitment: "confirmed" });
+
+    let campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), "1000000");
+
+    const pending = await program.account.pendingDeposit.fetch(pendingPda);
+    await waitForSlot(pending.requestedSlot.toNumber() + 1);
+
+    await program.methods
+      .confirmLargeDeposit()
+      .accounts({
+        donor: donor.publicKey,
+        campaign: campaignPda,
+        vaultLamports: vaultLamportsPda,
+        pendingDeposit: pendingPda,
+        contribution: findContributionPda(campaignPda, donor.publicKey),
+        donorProfile: findDonorProfilePda(donor.publicKey),
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([donor])
+      .rpc();
+
+    campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), "3000000");
+    assert.isNull(await provider.connection.getAccountInfo(pendingPda));
+  });
+
+  it("unconfirmed large deposit can be cancelled for a full refund", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const { campaignPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: Math.floor(0.05 * LAMPORTS_PER_SOL),
+      config: { largeDepositThreshold: new anchor.BN(1_000_000) },
+    });
+    const pendingPda = findPendingDepositPda(campaignPda, donor.publicKey);
+
+    const donorBefore = await provider.connection.getBalance(donor.publicKey);
+    await program.methods
+      .requestLargeDeposit(new anchor.BN(2_000_000))
+      .accounts({
+        donor: donor.publicKey,
+        campaign: campaignPda,
+        pendingDeposit: pendingPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([donor])
+      .rpc();
+    await program.methods
+      .cancelLargeDeposit()
+      .accounts({ donor: donor.publicKey, pendingDeposit: pendingPda })
+      .signers([donor])
+      .rpc();
+
+    const donorAfter = await provider.connection.getBalance(donor.publicKey);
+    const feeBuffer = 20_000;
+    assert.ok(donorBefore - donorAfter <= feeBuffer);
+    assert.isNull(await provider.connection.getAccountInfo(pendingPda));
+  });
+
+
+  it("vault_balance returns the vault's actual lamports", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: Math.floor(0.05 * LAMPORTS_PER_SOL),
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 1_000_000);
+
+    const balance = await program.methods
+      .vaultBalance()
+      .accounts({ campaign: campaignPda, vaultLamports: vaultLamportsPda })
+      .view();
+    assert.equal(
+      balance.toNumber(),
+      await provider.connection.getBalance(vaultLamportsPda)
+    );
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.ok(balance.toNumber() > campaign.funds.toNumber(), "includes rent");
+  });
+
+
+  it("enforced whitelist only allows registered beneficiaries", async () => {
+    const registered = Keypair.generate();
+    const unregistered = Keypair.generate();
+    const protocolConfig = await ensureProtocolConfig();
+
+    await program.methods
+      .setBeneficiaryAllowed(registered.publicKey, true)
+      .accounts({
+        admin: provider.wallet.publicKey,
+        protocolConfig,
+        beneficiaryRegistry: findBeneficiaryRegistryPda(registered.publicKey),
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    await setBeneficiaryWhitelist(true);
+    try {
+      await expectError(
+        initCampaign({
+          creator,
+          beneficiary: unregistered.publicKey,
+          targetLamports: 1_000_000,
+        }),
+        /BeneficiaryNotAllowed/i
+      );
+
+      const { campaignPda } = await initCampaign({
+        creator,
+        beneficiary: registered.publicKey,
+        targetLamports: 1_000_000,
+      });
+
+      await expectError(
+        program.methods
+          .updateBeneficiary()
+          .accounts({
+            authority: creator.publicKey,
+            campaign: campaignPda,
+            newBeneficiary: unregistered.publicKey,
+            protocolConfig,
+            beneficiaryRegistry: findBeneficiaryRegistryPda(
+              unregistered.publicKey
+            ),
+          })
+          .rpc(),
+        /BeneficiaryNotAllowed/i
+      );
+    } finally {
+      await setBeneficiaryWhitelist(false);
+    }
+
+    // Whitelist off: any beneficiary again
+    await initCampaign({
+      creator,
+      beneficiary: unregistered.publicKey,
+      targetLamports: 1_000_000,
+    });
+  });
+
+  it("update_beneficiary changes the payout address", async () => {
+    const beneficiary = Keypair.generate();
+    const replacement = Keypair.generate();
+
+    const { campaignPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+    });
+
+    await program.methods
+      .updateBeneficiary()
+      .accounts({
+        authority: creator.publicKey,
+        campaign: campaignPda,
+        newBeneficiary: replacement.publicKey,
+        protocolConfig: findProtocolConfigPda(),
+        beneficiaryRegistry: findBeneficiaryRegistryPda(replacement.publicKey),
+      })
+      .rpc();
+
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(
+      campaign.beneficiary.toBase58(),
+      replacement.publicKey.toBase58()
+    );
+  });
+
+
+  it("deposit of exactly the remaining amount fills the target without reduction", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const targetLamports = 1_000_000;
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports,
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 400_000);
+
+    const sig = await program.methods
+      .deposit(new anchor.BN(600_000))
+      .accounts({
+        donor: donor.publicKey,
+        campaign: campaignPda,
+        beneficiary: await campaignBeneficiary(campaignPda),
+        vaultLamports: vaultLamportsPda,
+        contribution: findContributionPda(campaignPda, donor.publicKey),
+        socialAttestation: await socialAttestationFor(campaignPda, donor.publicKey),
+        donorProfile: findDonorProfilePda(donor.publicKey),
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([donor])
+      .rpc({ commitment: "confirmed" });
+
+    const tx = await provider.connection.getTransaction(sig, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const logs = tx?.meta?.logMessages ?? [];
+    assert.isFalse(
+      logs.some((line) => line.includes("Deposit amount reduced")),
+      "exact fill must not log a reduction"
+    );
+
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), targetLamports.toString());
+
+    await expectError(
+      depositFrom(donor, campaignPda, vaultLamportsPda, 1),
+      /TargetAlreadyReached/i
+    );
+  });
+
+  it("backup beneficiary cannot finalize before deadline + backup delay", async () => {
+    const beneficiary = Keypair.generate();
+    const backup = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(backup.publicKey);
+    await airdrop(donor.publicKey);
+
+    const now = Math.floor(Date.now() / 1000);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+      config: {
+        ...defaultConfig(),
+        deadline: new anchor.BN(now + 3600),
+        backupBeneficiary: backup.publicKey,
+        backupDelay: new anchor.BN(60),
+      },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 500_000);
+
+    await expectError(
+      finalizeToBackup(backup, creator.publicKey, campaignPda, vaultLamportsPda),
+      /BackupNotYetAllowed/i
+    );
+
+    const stranger = Keypair.generate();
+    await airdrop(stranger.publicKey);
+    await expectError(
+      finalizeToBackup(stranger, creator.publicKey, campaignPda, vaultLamportsPda),
+      /Unauthorized/i
+    );
+  });
+
+  it("after the deadline deposits stop and the backup beneficiary can finalize", async () => {
+    const beneficiary = Keypair.generate();
+    const backup = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(backup.publicKey);
+    await airdrop(donor.publicKey);
+
+    const deadline = Math.floor(Date.now() / 1000) + 5;
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+      config: {
+        ...defaultConfig(),
+        deadline: new anchor.BN(deadline),
+        backupBeneficiary: backup.publicKey,
+        backupDelay: new anchor.BN(0),
+      },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 400_000);
+
+    await waitForUnixTime(deadline);
+    await expectError(
+      depositFrom(donor, campaignPda, vaultLamportsPda, 100_000),
+      /DeadlinePassed/i
+    );
+
+    const backupBefore = await provider.connection.getBalance(backup.publicKey);
+    await finalizeToBackup(backup, creator.publicKey, campaignPda, vaultLamportsPda);
+    const backupAfter = await provider.connection.getBalance(backup.publicKey);
+
+    // Backup pays the tx fee, so allow ~10k lamports of slack
+    assert.isAtLeast(backupAfter - backupBefore, 400_000 - 10_000);
+
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.isTrue(campaign.isFinalized);
+    assert.equal(campaign.finalFunds.toString(), "400000");
+  });
+
+  it("initialize rejects a backup beneficiary without a deadline", async () => {
+    await expectError(
+      initCampaign({
+        creator,
+        beneficiary: Keypair.generate().publicKey,
+        targetLamports: 1_000_000,
+        config: { ...defaultConfig(), backupBeneficiary: Keypair.generate().publicKey },
+      }),
+      /InvalidBackupBeneficiary/i
+    );
+  });
+
+  it("distribute_overflow rejects a campaign that is not above its target", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 1_000_000);
+
+    await expectError(
+      program.methods
+        .distributeOverflow(1)
+        .accounts({
+          campaign: campaignPda,
+          beneficiary: beneficiary.publicKey,
+          vaultLamports: vaultLamportsPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts([
+          {
+            pubkey: findContributionPda(campaignPda, donor.publicKey),
+            isWritable: true,
+            isSigner: false,
+          },
+          { pubkey: donor.publicKey, isWritable: true, isSigner: false },
+        ])
+        .rpc(),
+      /NoOverflow/i
+    );
+
+    const contribution = await program.account.contribution.fetch(
+      findContributionPda(campaignPda, donor.publicKey)
+    );
+    assert.equal(contribution.overflowRound, 0);
+  });
+
+  it("fixed-tier campaign accepts only configured amounts without clamping", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+      config: {
+        ...defaultConfig(),
+        allowedAmounts: [100_000, 250_000, 500_000, 0].map((amount) => new anchor.BN(amount)),
+      },
+    });
+
+    await expectError(
+      depositFrom(donor, campaignPda, vaultLamportsPda, 300_000),
+      /InvalidTier/i
+    );
+
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 500_000);
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 250_000);
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 100_000);
+
+    // 150_000 left: the 250_000 tier is rejected instead of being reduced
+    await expectError(
+      depositFrom(donor, campaignPda, vaultLamportsPda, 250_000),
+      /TierExceedsRemaining/i
+    );
+
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), "850000");
+  });
+
+  it("finalize emits CampaignCompleted and pays the fee to the authority", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+      config: { ...defaultConfig(), feeBps: 500 },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 1_000_000);
+
+    const beneficiaryBefore = await provider.connection.getBalance(beneficiary.publicKey);
+    const sig = await program.methods
+      .finalize()
+      .accounts({
+        beneficiary: beneficiary.publicKey,
+        authority: creator.publicKey,
+        campaign: campaignPda,
+        vaultLamports: vaultLamportsPda,
+        globalStats: findGlobalStatsPda(),
+        creatorProfile: findCreatorProfilePda(creator.publicKey),
+        protocolConfig: findProtocolConfigPda(),
+        treasury: await protocolTreasury(),
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([beneficiary])
+      .rpc({ commitment: "confirmed" });
+
+    const tx = await provider.connection.getTransaction(sig, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const beneficiaryAfter = await provider.connection.getBalance(beneficiary.publicKey);
+    assert.equal(beneficiaryAfter - beneficiaryBefore, 950_000 - (tx?.meta?.fee ?? 0));
+
+    const parser = new anchor.EventParser(program.programId, program.coder);
+    const events = [...parser.parseLogs(tx?.meta?.logMessages ?? [])];
+    const completed = events.find((event) => event.name === "campaignCompleted");
+    assert.isDefined(completed);
+    assert.equal(completed!.data.totalRaised.toString(), "1000000");
+    assert.equal(completed!.data.target.toString(), "1000000");
+    assert.equal(completed!.data.donorCount, 1);
+    assert.equal(completed!.data.beneficiary.toBase58(), beneficiary.publicKey.toBase58());
+    assert.equal(completed!.data.feePaid.toString(), "50000");
+    assert.isAtLeast(completed!.data.durationSeconds.toNumber(), 0);
+  });
+
+  it("block_beneficiary_deposits rejects the beneficiary as donor", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+      config: { ...defaultConfig(), blockBeneficiaryDeposits: true },
+    });
+
+    await expectError(
+      depositFrom(beneficiary, campaignPda, vaultLamportsPda, 100_000),
+      /BeneficiaryCannotDonate/i
+    );
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 100_000);
+
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), "100000");
+  });
+
+  it("beneficiary may donate when block_beneficiary_deposits is off", async () => {
+    const beneficiary = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+    });
+    await depositFrom(beneficiary, campaignPda, vaultLamportsPda, 100_000);
+
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), "100000");
+  });
+
+  it("team campaign vests the payout and members claim their split", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    const alice = Keypair.generate();
+    const bob = Keypair.generate();
+    for (const kp of [beneficiary, donor, alice, bob]) await airdrop(kp.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_001,
+      config: teamConfig([
+        { member: alice.publicKey, shareBps: 6_000 },
+        { member: bob.publicKey, shareBps: 4_000 },
+      ]),
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 1_000_001);
+
+    await expectError(
+      finalizeBy(beneficiary, creator.publicKey, campaignPda, vaultLamportsPda),
+      /TeamCampaignRequiresVesting/i
+    );
+
+    await finalizeTeamVested(beneficiary, campaignPda, vaultLamportsPda, 0, 1);
+    const teamVestingPda = findTeamVestingPda(campaignPda);
+    const vesting = await program.account.teamVesting.fetch(teamVestingPda);
+    assert.equal(vesting.total.toString(), "1000001");
+
+    await waitForUnixTime(vesting.start.toNumber() + 1);
+    await claimVested(alice, teamVestingPda);
+    await claimVested(bob, teamVestingPda);
+
+    const aliceStream = await program.account.vestingStream.fetch(
+      findVestingStreamPda(teamVestingPda, alice.publicKey)
+    );
+    const bobStream = await program.account.vestingStream.fetch(
+      findVestingStreamPda(teamVestingPda, bob.publicKey)
+    );
+    // Bob is the last member and picks up the rounding dust
+    assert.equal(aliceStream.claimed.toString(), "600000");
+    assert.equal(bobStream.claimed.toString(), "400001");
+
+    await expectError(claimVested(alice, teamVestingPda), /NothingToClaim/i);
+    const stranger = Keypair.generate();
+    await airdrop(stranger.publicKey);
+    await expectError(claimVested(stranger, teamVestingPda), /NotTeamMember/i);
+  });
+
+  it("team vesting releases nothing before the cliff", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    const alice = Keypair.generate();
+    for (const kp of [beneficiary, donor, alice]) await airdrop(kp.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+      config: teamConfig([{ member: alice.publicKey, shareBps: 10_000 }]),
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 500_000);
+
+    await expectError(
+      finalizeTeamVested(beneficiary, campaignPda, vaultLamportsPda, 10, 5),
+      /InvalidVestingSchedule/i
+    );
+    await finalizeTeamVested(beneficiary, campaignPda, vaultLamportsPda, 3_600, 7_200);
+    await expectError(claimVested(alice, findTeamVestingPda(campaignPda)), /NothingToClaim/i);
+  });
+
+  it("initialize rejects team shares that do not sum to 10000 bps", async () => {
+    await expectError(
+      initCampaign({
+        creator,
+        beneficiary: Keypair.generate().publicKey,
+        targetLamports: 1_000_000,
+        config: teamConfig([
+          { member: Keypair.generate().publicKey, shareBps: 5_000 },
+          { member: Keypair.generate().publicKey, shareBps: 4_000 },
+        ]),
+      }),
+      /InvalidTeamShares/i
+    );
+  });
+
+  it("anonymous deposit can be revealed into the donor's contribution", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+    });
+
+    const salt = randomBytes(32);
+    const commitment = donorCommitment(donor.publicKey, salt);
+    await depositAnonymous(donor, campaignPda, vaultLamportsPda, 300_000, commitment);
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 200_000);
+
+    let campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), "500000");
+    // The unrevealed commitment is not a donor yet
+    assert.equal(campaign.donorCount, 1);
+
+    await revealContribution(donor, campaignPda, commitment, salt);
+
+    const contribution = await program.account.contribution.fetch(
+      findContributionPda(campaignPda, donor.publicKey)
+    );
+    assert.equal(contribution.amount.toString(), "500000");
+    // The anonymous deposit came first
+    assert.equal(contribution.receiptNumber.toString(), "1");
+    campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.donorCount, 1);
+    assert.isNull(
+      await provider.connection.getAccountInfo(findAnonymousDepositPda(campaignPda, commitment))
+    );
+  });
+
+  it("only the original donor can reveal an anonymous deposit", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    const impostor = Keypair.generate();
+    await airdrop(donor.publicKey);
+    await airdrop(impostor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+    });
+
+    const salt = randomBytes(32);
+    const commitment = donorCommitment(donor.publicKey, salt);
+    await depositAnonymous(donor, campaignPda, vaultLamportsPda, 300_000, commitment);
+
+    await expectError(
+      revealContribution(impostor, campaignPda, commitment, salt),
+      /InvalidReveal/i
+    );
+    await expectError(
+      revealContribution(donor, campaignPda, commitment, randomBytes(32)),
+      /InvalidReveal/i
+    );
+  });
+
+  it("urgency_bucket reflects time to the deadline", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const bucketFor = async (deadline: number) => {
+      const { campaignPda } = await initCampaign({
+        creator,
+        beneficiary: Keypair.generate().publicKey,
+        targetLamports: 1_000_000,
+        config: { ...defaultConfig(), deadline: new anchor.BN(deadline) },
+      });
+      return program.methods.urgencyBucket().accounts({ campaign: campaignPda }).view();
+    };
+
+    assert.equal(await bucketFor(0), 4);
+    assert.equal(await bucketFor(now + 3_600), 1);
+    assert.equal(await bucketFor(now + 3 * 86_400), 2);
+    assert.equal(await bucketFor(now + 10 * 86_400), 3);
+    assert.equal(await bucketFor(now + 60 * 86_400), 4);
+  });
+
+  it("urgency_bucket reports expired campaigns as 0", async () => {
+    const deadline = Math.floor(Date.now() / 1000) + 3;
+    const { campaignPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+      config: { ...defaultConfig(), deadline: new anchor.BN(deadline) },
+    });
+
+    await waitForUnixTime(deadline);
+    assert.equal(
+      await program.methods.urgencyBucket().accounts({ campaign: campaignPda }).view(),
+      0
+    );
+  });
+
+  it("failed campaign returns the match to the matcher and contributions to donors", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const deadline = Math.floor(Date.now() / 1000) + 5;
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 2_000_000,
+      config: {
+        deadline: new anchor.BN(deadline),
+        matchTiers: [
+          { amount: new anchor.BN(500_000), rateBps: 10_000 },
+          { amount: new anchor.BN(0), rateBps: 0 },
+          { amount: new anchor.BN(0), rateBps: 0 },
+        ],
+      },
+    });
+    await fundMatch(creator, campaignPda, vaultLamportsPda, 800_000);
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 400_000);
+
+    let campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.totalMatched.toString(), "400000");
+    await expectError(
+      reclaimMatch(creator, campaignPda, vaultLamportsPda),
+      /CampaignStillOpen/i
+    );
+    await expectError(markFailed(campaignPda), /CampaignStillOpen/i);
+
+    await waitForUnixTime(deadline);
+    const statsBefore = await fetchGlobalStats();
+    await markFailed(campaignPda);
+    const statsAfter = await fetchGlobalStats();
+    assert.equal(
+      statsAfter.failedCampaigns.sub(statsBefore.failedCampaigns).toString(),
+      "1"
+    );
+
+    // Credited match (400_000) plus the unused pool (400_000)
+    const vaultBefore = await provider.connection.getBalance(vaultLamportsPda);
+    await reclaimMatch(creator, campaignPda, vaultLamportsPda);
+    const vaultAfter = await provider.connection.getBalance(vaultLamportsPda);
+    assert.equal(vaultBefore - vaultAfter, 800_000);
+
+    campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), "400000");
+    assert.isTrue(campaign.matchReclaimed);
+
+    await refundFrom(donor, campaignPda, vaultLamportsPda);
+    campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), "0");
+    await expectError(
+      finalizeBy(beneficiary, creator.publicKey, campaignPda, vaultLamportsPda),
+      /CampaignFailed/i
+    );
+  });
+
+  it("mark_failed rejects a campaign that reached its target", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const deadline = Math.floor(Date.now() / 1000) + 3;
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+      config: { deadline: new anchor.BN(deadline) },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 1_000_000);
+
+    await waitForUnixTime(deadline);
+    await expectError(markFailed(campaignPda), /TargetAlreadyReached/i);
+  });
+
+  it("process_deadline auto-finalizes to the beneficiary exactly once", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const deadline = Math.floor(Date.now() / 1000) + 4;
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+      config: { deadline: new anchor.BN(deadline), endAction: END_ACTION_AUTO_FINALIZE },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 1_000_000);
+
+    await expectError(
+      processDeadline(campaignPda, beneficiary.publicKey, vaultLamportsPda),
+      /CampaignStillOpen/i
+    );
+
+    await waitForUnixTime(deadline);
+    await processDeadline(campaignPda, beneficiary.publicKey, vaultLamportsPda);
+    assert.equal(await provider.connection.getBalance(beneficiary.publicKey), 1_000_000);
+
+    const statsBefore = await fetchGlobalStats();
+    await processDeadline(campaignPda, beneficiary.publicKey, vaultLamportsPda);
+    const statsAfter = await fetchGlobalStats();
+    assert.equal(
+      statsAfter.finalizedCampaigns.toString(),
+      statsBefore.finalizedCampaigns.toString()
+    );
+
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.isTrue(campaign.isFinalized);
+    assert.equal(campaign.finalFunds.toString(), "1000000");
+  });
+
+  it("process_deadline can enable refunds or keep the campaign open", async () => {
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const deadline = Math.floor(Date.now() / 1000) + 4;
+    const refunding = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+      config: { deadline: new anchor.BN(deadline), endAction: END_ACTION_ENABLE_REFUNDS },
+    });
+    const openEnded = Keypair.generate();
+    const keepOpen = await initCampaign({
+      creator,
+      beneficiary: openEnded.publicKey,
+      targetLamports: 1_000_000,
+      config: { deadline: new anchor.BN(deadline), endAction: END_ACTION_KEEP_OPEN },
+    });
+    await depositFrom(donor, refunding.campaignPda, refunding.vaultLamportsPda, 300_000);
+
+    await waitForUnixTime(deadline);
+    const refundingCampaign = await program.account.campaign.fetch(refunding.campaignPda);
+    await processDeadline(
+      refunding.campaignPda,
+      refundingCampaign.beneficiary,
+      refunding.vaultLamportsPda
+    );
+    await refundFrom(donor, refunding.campaignPda, refunding.vaultLamportsPda);
+    const refunded = await program.account.contribution.fetch(
+      findContributionPda(refunding.campaignPda, donor.publicKey)
+    );
+    assert.equal(refunded.amount.toString(), "0");
+
+    await processDeadline(keepOpen.campaignPda, openEnded.publicKey, keepOpen.vaultLamportsPda);
+    await depositFrom(donor, keepOpen.campaignPda, keepOpen.vaultLamportsPda, 200_000);
+    const campaign = await program.account.campaign.fetch(keepOpen.campaignPda);
+    assert.equal(campaign.funds.toString(), "200000");
+    assert.isTrue(campaign.deadlineProcessed);
+    assert.isFalse(campaign.isFinalized);
+  });
+
+  it("initialize rejects an unknown end action", async () => {
+    await expectError(
+      initCampaign({
+        creator,
+        beneficiary: Keypair.generate().publicKey,
+        targetLamports: 1_000_000,
+        config: { endAction: 5 },
+      }),
+      /InvalidEndAction/i
+    );
+  });
+
+  it("verbose_logs emits a DEPOSIT|campaign|donor|amount|total line", async () => {
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+      config: { verboseLogs: true },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 300_000);
+    const logs = await depositLogs(donor, campaignPda, vaultLamportsPda, 900_000);
+
+    // Second deposit is clamped to the 700_000 left
+    assert.include(
+      logs,
+      `Program log: DEPOSIT|${campaignPda.toBase58()}|${donor.publicKey.toBase58()}|700000|1000000`
+    );
+  });
+
+  it("deposit logs no DEPOSIT line without verbose_logs", async () => {
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+    });
+    const logs = await depositLogs(donor, campaignPda, vaultLamportsPda, 300_000);
+    assert.isFalse(logs.some((line) => line.includes("DEPOSIT|")));
+  });
+
+  it("withdraw waits for the floor and keeps the reserve", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+      config: {
+        allowPartialWithdrawals: true,
+        withdrawFloor: new anchor.BN(500_000),
+        withdrawReserve: new anchor.BN(200_000),
+      },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 400_000);
+    await expectError(
+      withdrawBy(beneficiary, campaignPda, vaultLamportsPda, 100_000),
+      /WithdrawFloorNotMet/i
+    );
+
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 200_000);
+    await expectError(
+      withdrawBy(beneficiary, campaignPda, vaultLamportsPda, 450_000),
+      /WithdrawReserveRequired/i
+    );
+    await withdrawBy(beneficiary, campaignPda, vaultLamportsPda, 400_000);
+
+    let campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), "200000");
+    assert.equal(campaign.totalWithdrawn.toString(), "400000");
+
+    // Withdrawn lamports still count towards the target
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 1_000_000);
+    campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), "600000");
+  });
+
+  it("withdraw is rejected unless partial withdrawals are enabled", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 500_000);
+    await expectError(
+      withdrawBy(beneficiary, campaignPda, vaultLamportsPda, 100_000),
+      /WithdrawalsDisabled/i
+    );
+  });
+
+  it("export_state blob can be imported into a fresh campaign", async () => {
+    const beneficiary = Keypair.generate();
+
+    const source = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+      config: { feeBps: 250 },
+    });
+    await fundMatch(creator, source.campaignPda, source.vaultLamportsPda, 300_000);
+
+    const blob: Buffer = await program.methods
+      .exportState()
+      .accounts({ campaign: source.campaignPda })
+      .view();
+    assert.equal(blob[0], 1);
+
+    const dest = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 5_000_000,
+    });
+
+    await expectError(
+      importState(dest.campaignPda, dest.vaultLamportsPda, blob, beneficiary.publicKey),
+      /ImportUnderfunded/i
+    );
+    await expectError(
+      importState(dest.campaignPda, dest.vaultLamportsPda, blob, Keypair.generate().publicKey),
+      /ImportBeneficiaryMismatch/i
+    );
+
+    await airdrop(dest.vaultLamportsPda, 300_000);
+    await importState(dest.campaignPda, dest.vaultLamportsPda, blob, beneficiary.publicKey);
+
+    const sourceCampaign = await program.account.campaign.fetch(source.campaignPda);
+    const imported = await program.account.campaign.fetch(dest.campaignPda);
+    assert.equal(imported.matchPool.toString(), "300000");
+    assert.equal(imported.target.toString(), "1000000");
+    assert.equal(imported.feeBps, 250);
+    assert.equal(imported.beneficiary.toBase58(), beneficiary.publicKey.toBase58());
+    assert.notEqual(imported.campaignId.toString(), sourceCampaign.campaignId.toString());
+
+    // The destination now has activity, so a second import is refused
+    await expectError(
+      importState(dest.campaignPda, dest.vaultLamportsPda, blob, beneficiary.publicKey),
+      /CampaignNotFresh/i
+    );
+  });
+
+  it("import_state refuses deposits and closed campaigns", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const funded = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+    });
+    await depositFrom(donor, funded.campaignPda, funded.vaultLamportsPda, 300_000);
+    const fundedBlob: Buffer = await program.methods
+      .exportState()
+      .accounts({ campaign: funded.campaignPda })
+      .view();
+
+    const cancelled = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+    });
+    await cancelCampaign(creator, cancelled.campaignPda);
+    const cancelledBlob: Buffer = await program.methods
+      .exportState()
+      .accounts({ campaign: cancelled.campaignPda })
+      .view();
+
+    const dest = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+    });
+    await airdrop(dest.vaultLamportsPda, 300_000);
+    await expectError(
+      importState(dest.campaignPda, dest.vaultLamportsPda, fundedBlob, beneficiary.publicKey),
+      /ImportHasDeposits/i
+    );
+    await expectError(
+      importState(dest.campaignPda, dest.vaultLamportsPda, cancelledBlob, beneficiary.publicKey),
+      /ImportNotOpen/i
+    );
+  });
+
+  it("import_state rejects an unknown blob version", async () => {
+    const source = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+    });
+    const blob: Buffer = await program.methods
+      .exportState()
+      .accounts({ campaign: source.campaignPda })
+      .view();
+
+    const dest = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+    });
+    const tampered = Buffer.from(blob);
+    tampered[0] = 99;
+    await expectError(
+      importState(dest.campaignPda, dest.vaultLamportsPda, tampered, Keypair.generate().publicKey),
+      /UnsupportedStateVersion/i
+    );
+    await expectError(
+      importState(
+        dest.campaignPda,
+        dest.vaultLamportsPda,
+        blob.subarray(0, 10),
+        Keypair.generate().publicKey
+      ),
+      /InvalidStateBlob/i
+    );
+  });
+
+  it("clamped deposit emits DepositClamped with requested and accepted amounts", async () => {
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+    });
+    const parser = new anchor.EventParser(program.programId, program.coder);
+
+    const exact = [...parser.parseLogs(
+      await depositLogs(donor, campaignPda, vaultLamportsPda, 400_000)
+    )];
+    assert.isFalse(exact.some((event) => event.name === "depositClamped"));
+
+    const events = [...parser.parseLogs(
+      await depositLogs(donor, campaignPda, vaultLamportsPda, 900_000)
+    )];
+    const clamped = events.find((event) => event.name === "depositClamped");
+    assert.isDefined(clamped);
+    assert.equal(clamped!.data.donor.toBase58(), donor.publicKey.toBase58());
+    assert.equal(clamped!.data.requested.toString(), "900000");
+    assert.equal(clamped!.data.accepted.toString(), "600000");
+  });
+
+  it("finalize below min_finalize_amount sweeps the dust to the authority", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+      config: { minFinalizeAmount: new anchor.BN(10_000) },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 5_000);
+
+    const profilePda = findCreatorProfilePda(creator.publicKey);
+    const profileBefore = await program.account.creatorProfile.fetch(profilePda);
+    const beneficiaryBefore = await provider.connection.getBalance(beneficiary.publicKey);
+    const logs = await finalizeLogs(beneficiary, campaignPda, vaultLamportsPda);
+    const beneficiaryAfter = await provider.connection.getBalance(beneficiary.publicKey);
+    // The beneficiary only pays the tx fee
+    assert.isBelow(beneficiaryAfter, beneficiaryBefore);
+
+    const parser = new anchor.EventParser(program.programId, program.coder);
+    const events = [...parser.parseLogs(logs)];
+    const swept = events.find((event) => event.name === "dustSwept");
+    assert.isDefined(swept);
+    assert.equal(swept!.data.amount.toString(), "5000");
+    assert.isFalse(events.some((event) => event.name === "campaignCompleted"));
+
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.isTrue(campaign.isFinalized);
+    assert.equal(await provider.connection.getBalance(vaultLamportsPda), 0);
+
+    // The creator is booked a failure with nothing raised
+    const profileAfter = await program.account.creatorProfile.fetch(profilePda);
+    assert.equal(profileAfter.campaignsFailed, profileBefore.campaignsFailed + 1);
+    assert.equal(profileAfter.totalRaised.toString(), profileBefore.totalRaised.toString());
+  });
+
+  it("finalize at or above min_finalize_amount pays the beneficiary as usual", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+      config: { minFinalizeAmount: new anchor.BN(10_000) },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 10_000);
+
+    const logs = await finalizeLogs(beneficiary, campaignPda, vaultLamportsPda);
+    const parser = new anchor.EventParser(program.programId, program.coder);
+    const events = [...parser.parseLogs(logs)];
+    assert.isTrue(events.some((event) => event.name === "campaignCompleted"));
+    assert.isFalse(events.some((event) => event.name === "dustSwept"));
+  });
+
+  it("initialize rejects a min_finalize_amount above the target", async () => {
+    await expectError(
+      initCampaign({
+        creator,
+        beneficiary: Keypair.generate().publicKey,
+        targetLamports: 1_000_000,
+        config: { minFinalizeAmount: new anchor.BN(2_000_000) },
+      }),
+      /InvalidMinFinalizeAmount/i
+    );
+  });
+
+  it("max_per_donor caps a donor's total and donor_remaining reports the headroom", async () => {
+    const donor = Keypair.generate();
+    const other = Keypair.generate();
+    await airdrop(donor.publicKey);
+    await airdrop(other.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+      config: { maxPerDonor: new anchor.BN(300_000) },
+    });
+
+    assert.equal((await donorRemaining(campaignPda, donor.publicKey)).toString(), "300000");
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 200_000);
+    assert.equal((await donorRemaining(campaignPda, donor.publicKey)).toString(), "100000");
+
+    // Clamped to the 100_000 the cap still allows
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 500_000);
+    const contribution = await program.account.contribution.fetch(
+      findContributionPda(campaignPda, donor.publicKey)
+    );
+    assert.equal(contribution.amount.toString(), "300000");
+    await expectError(
+      depositFrom(donor, campaignPda, vaultLamportsPda, 1),
+      /DonorCapReached/i
+    );
+    assert.equal((await donorRemaining(campaignPda, donor.publicKey)).toString(), "0");
+    assert.equal((await donorRemaining(campaignPda, other.publicKey)).toString(), "300000");
+  });
+
+  it("donor_remaining without a cap is the campaign remaining", async () => {
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 250_000);
+    assert.equal((await donorRemaining(campaignPda, donor.publicKey)).toString(), "750000");
+  });
+
+  it("finalize_and_reward creates a reward mint and backers claim once", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    const other = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+    await airdrop(donor.publicKey);
+    await airdrop(other.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+      config: { rewardRate: new anchor.BN(3) },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 400_000);
+    await depositFrom(other, campaignPda, vaultLamportsPda, 600_000);
+
+    const before = await provider.connection.getBalance(beneficiary.publicKey);
+    await finalizeAndReward(beneficiary, campaignPda, vaultLamportsPda);
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.isTrue(campaign.isFinalized);
+    assert.isTrue(campaign.rewardsReady);
+    const mintRent = await provider.connection.getMinimumBalanceForRentExemption(82);
+    const after = await provider.connection.getBalance(beneficiary.publicKey);
+    assert.equal(after - before, 1_000_000 - mintRent);
+
+    const destination = await createTokenAccount(findRewardMintPda(campaignPda), donor.publicKey);
+    await claimReward(donor, campaignPda, destination);
+    const balance = await provider.connection.getTokenAccountBalance(destination);
+    assert.equal(balance.value.amount, "1200000");
+    assert.equal(balance.value.decimals, 9);
+
+    await expectError(claimReward(donor, campaignPda, destination), /RewardAlreadyClaimed/i);
+  });
+
+  it("finalize_and_reward requires a reward rate", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 500_000);
+    await expectError(finalizeAndReward(beneficiary, campaignPda, vaultLamportsPda), /NoRewards/i);
+  });
+
+  it("overshoot_tolerance_bps lets deposits exceed the target up to the tolerance", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    const other = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+    await airdrop(donor.publicKey);
+    await airdrop(other.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+      config: { overshootToleranceBps: 500 },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 900_000);
+    // Clamped to the 1_050_000 ceiling, not the target
+    await depositFrom(other, campaignPda, vaultLamportsPda, 200_000);
+    let campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), "1050000");
+    assert.equal((await donorRemaining(campaignPda, donor.publicKey)).toString(), "0");
+    await expectError(
+      depositFrom(donor, campaignPda, vaultLamportsPda, 1),
+      /TargetAlreadyReached/i
+    );
+
+    const before = await provider.connection.getBalance(beneficiary.publicKey);
+    await finalizeBy(beneficiary, provider.wallet.publicKey, campaignPda, vaultLamportsPda);
+    const after = await provider.connection.getBalance(beneficiary.publicKey);
+    assert.equal(after - before, 1_050_000);
+    campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.finalFunds.toString(), "1050000");
+  });
+
+  it("initialize validates overshoot_tolerance_bps", async () => {
+    await expectError(
+      initCampaign({
+        creator,
+        beneficiary: Keypair.generate().publicKey,
+        targetLamports: 1_000_000,
+        config: { overshootToleranceBps: 10_001 },
+      }),
+      /InvalidOvershootTolerance/i
+    );
+    await expectError(
+      initCampaign({
+        creator,
+        beneficiary: Keypair.generate().publicKey,
+        targetLamports: 1_000_000,
+        config: { overshootToleranceBps: 100, strictTarget: true },
+      }),
+      /InvalidOvershootTolerance/i
+    );
+  });
+
+  it("finalize splits the vault remainder with the protocol treasury", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    const treasury = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+    await airdrop(donor.publicKey);
+    await airdrop(treasury.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 500_000);
+    const remainder = (await provider.connection.getBalance(vaultLamportsPda)) - 500_000;
+
+    await expectError(setRentSplit(treasury.publicKey, 10_001), /InvalidRentSplit/i);
+    await setRentSplit(treasury.publicKey, 2_500);
+    try {
+      // Only the configured treasury may take the split
+      await expectError(
+        program.methods
+          .finalize()
+          .accounts({
+            beneficiary: beneficiary.publicKey,
+            authority: creator.publicKey,
+            campaign: campaignPda,
+            vaultLamports: vaultLamportsPda,
+            globalStats: findGlobalStatsPda(),
+            creatorProfile: findCreatorProfilePda(creator.publicKey),
+            protocolConfig: findProtocolConfigPda(),
+            treasury: Keypair.generate().publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([beneficiary])
+          .rpc(),
+        /InvalidTreasury/i
+      );
+
+      const before = await provider.connection.getBalance(treasury.publicKey);
+      await finalizeBy(beneficiary, creator.publicKey, campaignPda, vaultLamportsPda);
+      const after = await provider.connection.getBalance(treasury.publicKey);
+      assert.equal(after - before, Math.floor((remainder * 2_500) / 10_000));
+      assert.equal(await provider.connection.getBalance(vaultLamportsPda), 0);
+    } finally {
+      await setRentSplit(creator.publicKey, 0);
+    }
+  });
+
+  it("finalize with a zero rent split pays the treasury nothing", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    const treasury = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+    await airdrop(donor.publicKey);
+    await airdrop(treasury.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 500_000);
+
+    await setRentSplit(treasury.publicKey, 0);
+    try {
+      const before = await provider.connection.getBalance(treasury.publicKey);
+      await finalizeBy(beneficiary, creator.publicKey, campaignPda, vaultLamportsPda);
+      assert.equal(await provider.connection.getBalance(treasury.publicKey), before);
+      assert.equal(await provider.connection.getBalance(vaultLamportsPda), 0);
+    } finally {
+      await setRentSplit(creator.publicKey, 0);
+    }
+  });
+
+  it("round_up_to_tier raises a deposit to the next tier and emits DepositRoundedUp", async () => {
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+      config: {
+        allowedAmounts: [
+          new anchor.BN(100_000),
+          new anchor.BN(250_000),
+          new anchor.BN(0),
+          new anchor.BN(0),
+        ],
+        roundUpToTier: true,
+      },
+    });
+    const parser = new anchor.EventParser(program.programId, program.coder);
+
+    const events = [...parser.parseLogs(
+      await depositLogs(donor, campaignPda, vaultLamportsPda, 120_000)
+    )];
+    const rounded = events.find((event) => event.name === "depositRoundedUp");
+    assert.isDefined(rounded);
+    assert.equal(rounded.data.requested.toString(), "120000");
+    assert.equal(rounded.data.rounded.toString(), "250000");
+
+    // Above every tier the exact amount is taken
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 300_000);
+    let campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), "550000");
+
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 120_000);
+    // 200_000 left: rounding 150_000 up to 250_000 would pass the target, so the exact amount is taken
+    const fallback = [...parser.parseLogs(
+      await depositLogs(donor, campaignPda, vaultLamportsPda, 150_000)
+    )];
+    assert.isFalse(fallback.some((event) => event.name === "depositRoundedUp"));
+    campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), "950000");
+  });
+
+  it("initialize rejects round_up_to_tier without tiers", async () => {
+    await expectError(
+      initCampaign({
+        creator,
+        beneficiary: Keypair.generate().publicKey,
+        targetLamports: 1_000_000,
+        config: { roundUpToTier: true },
+      }),
+      /RoundUpWithoutTiers/i
+    );
+  });
+
+  it("max_total_withdrawals caps partial withdrawals at the boundary", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+      config: {
+        allowPartialWithdrawals: true,
+        maxTotalWithdrawals: new anchor.BN(300_000),
+      },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 800_000);
+
+    await withdrawBy(beneficiary, campaignPda, vaultLamportsPda, 200_000);
+    await expectError(
+      withdrawBy(beneficiary, campaignPda, vaultLamportsPda, 100_001),
+      /WithdrawCapReached/i
+    );
+    // Exactly up to the cap is fine, one more lamport is not
+    await withdrawBy(beneficiary, campaignPda, vaultLamportsPda, 100_000);
+    await expectError(
+      withdrawBy(beneficiary, campaignPda, vaultLamportsPda, 1),
+      /WithdrawCapReached/i
+    );
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.totalWithdrawn.toString(), "300000");
+    assert.equal(campaign.funds.toString(), "500000");
+
+    // finalize still pays out what is left
+    await finalizeBy(beneficiary, creator.publicKey, campaignPda, vaultLamportsPda);
+  });
+
+  it("initialize rejects max_total_withdrawals above the target", async () => {
+    await expectError(
+      initCampaign({
+        creator,
+        beneficiary: Keypair.generate().publicKey,
+        targetLamports: 1_000_000,
+        config: { maxTotalWithdrawals: new anchor.BN(1_000_001) },
+      }),
+      /InvalidWithdrawConfig/i
+    );
+  });
+
+  it("health_score combines progress, velocity, backers and time", async () => {
+    const donor = Keypair.generate();
+    const other = Keypair.generate();
+    await airdrop(donor.publicKey);
+    await airdrop(other.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+    });
+    const score = () => program.methods.healthScore().accounts({ campaign: campaignPda }).view();
+
+    // No deadline: velocity follows progress and time is full
+    assert.equal(await score(), 10);
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 300_000);
+    await depositFrom(other, campaignPda, vaultLamportsPda, 200_000);
+    // 40% * 50 + 30% * 50 + 20% * (2 / 20) + 10% * 100
+    assert.equal(await score(), 47);
+  });
+
+  it("health_score is 0 for a cancelled campaign", async () => {
+    const { campaignPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+    });
+    await cancelCampaign(creator, campaignPda);
+    assert.equal(
+      await program.methods.healthScore().accounts({ campaign: campaignPda }).view(),
+      0
+    );
+  });
+
+  it("refund policy lockup and window bound when a donor may refund", async () => {
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+      config: {
+        refundPolicy: {
+          ...defaultRefundPolicy(),
+          refundsEnabled: true,
+          lockupSeconds: new anchor.BN(2),
+          refundWindow: new anchor.BN(4),
+        },
+      },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 300_000);
+    const contribution = await program.account.contribution.fetch(
+      findContributionPda(campaignPda, donor.publicKey)
+    );
+    const start = contribution.firstContributedAt.toNumber();
+
+    await expectError(refundFrom(donor, campaignPda, vaultLamportsPda), /RefundLocked/i);
+    await waitForUnixTime(start + 2);
+    await refundFrom(donor, campaignPda, vaultLamportsPda);
+
+    // The window keeps counting from the first contribution
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 300_000);
+    await waitForUnixTime(start + 4);
+    await expectError(refundFrom(donor, campaignPda, vaultLamportsPda), /RefundWindowClosed/i);
+  });
+
+  it("refund policy max_total_refunds caps refunds across donors", async () => {
+    const donor = Keypair.generate();
+    const other = Keypair.generate();
+    await airdrop(donor.publicKey);
+    await airdrop(other.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+      config: {
+        refundPolicy: {
+          ...defaultRefundPolicy(),
+          refundsEnabled: true,
+          maxTotalRefunds: new anchor.BN(500_000),
+        },
+      },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 300_000);
+    await depositFrom(other, campaignPda, vaultLamportsPda, 300_000);
+
+    await refundFrom(donor, campaignPda, vaultLamportsPda);
+    await expectError(refundFrom(other, campaignPda, vaultLamportsPda), /RefundCapReached/i);
+
+    // Cancelled campaigns refund everyone regardless of the policy
+    await cancelCampaign(creator, campaignPda);
+    await refundFrom(other, campaignPda, vaultLamportsPda);
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.totalRefunded.toString(), "600000");
+  });
+
+  it("update_refund_policy validates and respects immutable", async () => {
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 300_000);
+    await expectError(refundFrom(donor, campaignPda, vaultLamportsPda), /RefundsDisabled/i);
+
+    await expectError(
+      updateRefundPolicy(campaignPda, {
+        ...defaultRefundPolicy(),
+        lockupSeconds: new anchor.BN(10),
+        refundWindow: new anchor.BN(10),
+      }),
+      /InvalidRefundPolicy/i
+    );
+    await updateRefundPolicy(campaignPda, {
+      ...defaultRefundPolicy(),
+      refundsEnabled: true,
+      immutable: true,
+    });
+    await expectError(
+      updateRefundPolicy(campaignPda, defaultRefundPolicy()),
+      /RefundPolicyImmutable/i
+    );
+    await refundFrom(donor, campaignPda, vaultLamportsPda);
+  });
+
+  it("unlock_content returns the gated hash only at the unlock threshold", async () => {
+    const donor = Keypair.generate();
+    const stranger = Keypair.generate();
+    await airdrop(donor.publicKey);
+    await airdrop(stranger.publicKey);
+
+    const contentHash = [...createHash("sha256").update("backer-only").digest()];
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+      config: { gatedContentHash: contentHash, unlockThreshold: new anchor.BN(100_000) },
+    });
+
+    await expectError(unlockContent(stranger, campaignPda), /ContentLocked/i);
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 99_999);
+    await expectError(unlockContent(donor, campaignPda), /ContentLocked/i);
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 1);
+    assert.deepEqual([...(await unlockContent(donor, campaignPda))], contentHash);
+  });
+
+  it("deposit stays well inside the default compute budget", async () => {
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+      config: {
+        matchTiers: [
+          { amount: new anchor.BN(500_000), rateBps: 5_000 },
+          { amount: new anchor.BN(0), rateBps: 0 },
+          { amount: new anchor.BN(0), rateBps: 0 },
+        ],
+      },
+    });
+    await fundMatch(creator, campaignPda, vaultLamportsPda, 100_000);
+
+    const computeUnits = async (lamports: number) => {
+      const sig = await depositFrom(donor, campaignPda, vaultLamportsPda, lamports);
+      await provider.connection.confirmTransaction(sig, "confirmed");
+      const tx = await provider.connection.getTransaction(sig, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      return tx?.meta?.computeUnitsConsumed ?? 0;
+    };
+
+    // First deposit creates the contribution; the second takes the plain path
+    const first = await computeUnits(200_000);
+    const repeat = await computeUnits(200_000);
+    assert.isAbove(repeat, 0);
+    assert.isBelow(first, 100_000);
+    assert.isBelow(repeat, 100_000);
+  });
+
+  it("update_fee changes fee_bps only before the first deposit", async () => {
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+      config: { feeBps: 100 },
+    });
+
+    await expectError(updateFee(campaignPda, 10_001), /InvalidFee/i);
+    await updateFee(campaignPda, 250);
+    let campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.feeBps, 250);
+
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 1);
+    await expectError(updateFee(campaignPda, 0), /FeeLocked/i);
+    campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.feeBps, 250);
+  });
+
+  it("post_comment extends the campaign's comment hash chain", async () => {
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 100_000);
+
+    let head = Buffer.alloc(32);
+    for (const text of ["first", "second"]) {
+      const commentHash = createHash("sha256").update(text).digest();
+      await postComment(donor, campaignPda, commentHash);
+      head = createHash("sha256")
+        .update(Buffer.concat([head, commentHash, donor.publicKey.toBuffer()]))
+        .digest();
+    }
+
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.commentCount.toString(), "2");
+    assert.deepEqual(Buffer.from(campaign.lastCommentHash), head);
+  });
+
+  it("post_comment is for donors only", async () => {
+    const donor = Keypair.generate();
+    const stranger = Keypair.generate();
+    await airdrop(donor.publicKey);
+    await airdrop(stranger.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+      config: { refundPolicy: { ...defaultRefundPolicy(), refundsEnabled: true } },
+    });
+    const commentHash = createHash("sha256").update("hello").digest();
+    await expectError(
+      postComment(stranger, campaignPda, commentHash),
+      /AccountNotInitialized|not initialized/i
+    );
+
+    // A refunded contribution no longer counts
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 100_000);
+    await refundFrom(donor, campaignPda, vaultLamportsPda);
+    await expectError(postComment(donor, campaignPda, commentHash), /NotADonor/i);
+  });
+
+  it("stream_payout forwards stream_bps of each deposit and finalize pays the reserve", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+      config: { streamPayout: true, streamBps: 3_000 },
+    });
+
+    const beneficiaryBefore = await provider.connection.getBalance(beneficiary.publicKey);
+    const vaultBefore = await provider.connection.getBalance(vaultLamportsPda);
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 400_000);
+    assert.equal(
+      (await provider.connection.getBalance(beneficiary.publicKey)) - beneficiaryBefore,
+      120_000
+    );
+    assert.equal((await provider.connection.getBalance(vaultLamportsPda)) - vaultBefore, 280_000);
+    let campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), "280000");
+    assert.equal(campaign.totalStreamed.toString(), "120000");
+
+    // Streamed lamports count towards the target
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 700_000);
+    campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), "700000");
+    assert.equal(campaign.totalStreamed.toString(), "300000");
+    await expectError(
+      depositFrom(donor, campaignPda, vaultLamportsPda, 1),
+      /TargetAlreadyReached/i
+    );
+
+    const before = await provider.connection.getBalance(beneficiary.publicKey);
+    await finalizeBy(beneficiary, creator.publicKey, campaignPda, vaultLamportsPda);
+    assert.equal((await provider.connection.getBalance(beneficiary.publicKey)) - before, 700_000);
+  });
+
+  it("stream_payout refunds only the reserved share after cancel", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+      config: { streamPayout: true, streamBps: 2_500 },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 400_000);
+    await expectError(
+      depositAnonymous(donor, campaignPda, vaultLamportsPda, 100_000, randomBytes(32)),
+      /StreamingDepositOnly/i
+    );
+    await cancelCampaign(creator, campaignPda);
+
+    const before = await provider.connection.getBalance(donor.publicKey);
+    await refundFrom(donor, campaignPda, vaultLamportsPda);
+    assert.equal((await provider.connection.getBalance(donor.publicKey)) - before, 300_000);
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), "0");
+  });
+
+  it("initialize rejects stream_payout without a stream share", async () => {
+    await expectError(
+      initCampaign({
+        creator,
+        beneficiary: Keypair.generate().publicKey,
+        targetLamports: 1_000_000,
+        config: { streamPayout: true },
+      }),
+      /InvalidStreamConfig/i
+    );
+  });
+
+  it("initialize rejects stream_payout with a large deposit threshold", async () => {
+    await expectError(
+      initCampaign({
+        creator,
+        beneficiary: Keypair.generate().publicKey,
+        targetLamports: 1_000_000,
+        config: {
+          streamPayout: true,
+          streamBps: 3_000,
+          largeDepositThreshold: new anchor.BN(500_000),
+        },
+      }),
+      /LargeDepositsUnconfirmable/i
+    );
+  });
+
+  it("verify_snapshot matches the state hash until the campaign changes", async () => {
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+    });
+    const verify = (hash: Buffer) =>
+      program.methods.verifySnapshot([...hash]).accounts({ campaign: campaignPda }).view();
+
+    // The state hash covers the serialized campaign after its 8-byte discriminator
+    const info = await provider.connection.getAccountInfo(campaignPda);
+    const snapshot = createHash("sha256").update(info!.data.subarray(8)).digest();
+    assert.isTrue(await verify(snapshot));
+    assert.isFalse(await verify(Buffer.alloc(32)));
+
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 100_000);
+    assert.isFalse(await verify(snapshot));
+  });
+
+  it("min_deposit_count counts deposits, not donors, before finalize", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+      config: { minDepositCount: 3 },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 100_000);
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 100_000);
+    await expectError(
+      finalizeBy(beneficiary, creator.publicKey, campaignPda, vaultLamportsPda),
+      /NotEnoughDeposits/i
+    );
+
+    // The same donor's third deposit is enough
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 100_000);
+    let campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.depositCount, 3);
+    assert.equal(campaign.donorCount, 1);
+    await finalizeBy(beneficiary, creator.publicKey, campaignPda, vaultLamportsPda);
+    campaign = await program.account.campaign.fetch(campaignPda);
+    assert.isTrue(campaign.isFinalized);
+  });
+
+  it("a donor dispute freezes finalize until the arbiter releases it", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    const arbiter = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+      config: { arbiter: arbiter.publicKey },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 400_000);
+
+    await raiseDispute(donor, campaignPda);
+    await expectError(raiseDispute(donor, campaignPda), /DisputeActive/i);
+    await expectError(
+      finalizeBy(beneficiary, creator.publicKey, campaignPda, vaultLamportsPda),
+      /DisputeActive/i
+    );
+    await expectError(resolveDispute(donor, campaignPda, false), /Unauthorized/i);
+
+    await resolveDispute(arbiter, campaignPda, false);
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.isFalse(campaign.disputeActive);
+    assert.equal(campaign.disputeCount, 1);
+    await finalizeBy(beneficiary, creator.publicKey, campaignPda, vaultLamportsPda);
+  });
+
+  it("resolving a dispute for the donors fails the campaign and opens refunds", async () => {
+    const donor = Keypair.generate();
+    const stranger = Keypair.generate();
+    const arbiter = Keypair.generate();
+    await airdrop(donor.publicKey);
+    await airdrop(stranger.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+      config: { arbiter: arbiter.publicKey },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 400_000);
+    await expectError(
+      raiseDispute(stranger, campaignPda),
+      /AccountNotInitialized|not initialized/i
+    );
+
+    await raiseDispute(donor, campaignPda);
+    const statsBefore = await fetchGlobalStats();
+    await resolveDispute(arbiter, campaignPda, true);
+    const statsAfter = await fetchGlobalStats();
+    assert.equal(
+      statsAfter.failedCampaigns.sub(statsBefore.failedCampaigns).toString(),
+      "1"
+    );
+
+    const before = await provider.connection.getBalance(donor.publicKey);
+    await refundFrom(donor, campaignPda, vaultLamportsPda);
+    assert.equal((await provider.connection.getBalance(donor.publicKey)) - before, 400_000);
+  });
+
+  it("disputes need an arbiter", async () => {
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 100_000);
+    await expectError(raiseDispute(donor, campaignPda), /NoArbiter/i);
+  });
+
+  it("creator profile books a successful finalize and feeds creator_reputation", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+    const profilePda = findCreatorProfilePda(creator.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+    });
+    const before = await program.account.creatorProfile.fetch(profilePda);
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 700_000);
+    await finalizeBy(beneficiary, creator.publicKey, campaignPda, vaultLamportsPda);
+
+    const after = await program.account.creatorProfile.fetch(profilePda);
+    assert.equal(after.creator.toBase58(), creator.publicKey.toBase58());
+    assert.equal(after.campaignsSucceeded, before.campaignsSucceeded + 1);
+    assert.equal(after.campaignsFailed, before.campaignsFailed);
+    assert.equal(after.totalRaised.sub(before.totalRaised).toString(), "700000");
+    assert.isAbove(after.campaignsCreated, 0);
+    assert.equal(await creatorReputation(campaignPda), expectedReputation(after));
+  });
+
+  it("mark_failed books a failure and the funds owed back on the creator profile", async () => {
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+    const profilePda = findCreatorProfilePda(creator.publicKey);
+
+    const deadline = Math.floor(Date.now() / 1000) + 3;
+    const before = await program.account.creatorProfile.fetch(profilePda);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+      config: { deadline: new anchor.BN(deadline) },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 300_000);
+    await waitForUnixTime(deadline);
+    await markFailed(campaignPda);
+
+    const after = await program.account.creatorProfile.fetch(profilePda);
+    assert.equal(after.campaignsCreated, before.campaignsCreated + 1);
+    assert.equal(after.campaignsFailed, before.campaignsFailed + 1);
+    assert.equal(after.campaignsSucceeded, before.campaignsSucceeded);
+    assert.equal(after.totalRefunded.sub(before.totalRefunded).toString(), "300000");
+    assert.equal(await creatorReputation(campaignPda), expectedReputation(after));
+  });
+
+  it("deposit_voucher moves escrowed lamports on a donor-signed voucher once", async () => {
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+    });
+    await fundVoucherEscrow(donor, 500_000);
+
+    const donorBefore = await provider.connection.getBalance(donor.publicKey);
+    await depositVoucher(donor, donor.publicKey, campaignPda, vaultLamportsPda, 300_000, 1);
+    // The relayer paid every fee and rent, the donor signed nothing on-chain
+    assert.equal(await provider.connection.getBalance(donor.publicKey), donorBefore);
+
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), "300000");
+    const contribution = await program.account.contribution.fetch(
+      findContributionPda(campaignPda, donor.publicKey)
+    );
+    assert.equal(contribution.amount.toString(), "300000");
+
+    // Replaying the same nonce fails on the spent nonce account
+    await expectError(
+      depositVoucher(donor, donor.publicKey, campaignPda, vaultLamportsPda, 300_000, 1),
+      /already in use/i
+    );
+    await expectError(
+      depositVoucher(donor, donor.publicKey, campaignPda, vaultLamportsPda, 300_000, 2),
+      /InsufficientVoucherEscrow/i
+    );
+  });
+
+  it("deposit_voucher rejects a tampered amount or a foreign signer", async () => {
+    const donor = Keypair.generate();
+    const mallory = Keypair.generate();
+    await airdrop(donor.publicKey);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+    });
+    await fundVoucherEscrow(donor, 500_000);
+
+    await expectError(
+      depositVoucher(donor, donor.publicKey, campaignPda, vaultLamportsPda, 400_000, 1, 100_000),
+      /InvalidVoucher/i
+    );
+    await expectError(
+      depositVoucher(mallory, donor.publicKey, campaignPda, vaultLamportsPda, 100_000, 1),
+      /InvalidVoucher/i
+    );
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), "0");
+  });
+
+  it("initialize validates dust_refund_threshold against the target", async () => {
+    await expectError(
+      initCampaign({
+        creator,
+        beneficiary: Keypair.generate().publicKey,
+        targetLamports: 1_000_000,
+        config: { dustRefundThreshold: new anchor.BN(1_000_001) },
+      }),
+      /InvalidDustRefundThreshold/i
+    );
+
+    const { campaignPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+      config: { dustRefundThreshold: new anchor.BN(5_000) },
+    });
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.dustRefundThreshold.toString(), "5000");
+    assert.equal(campaign.overflowDustRedirected.toString(), "0");
+  });
+
+  it("validate_config reports init errors without creating accounts", async () => {
+    const target = new anchor.BN(1_000_000);
+    const validate = (config: Partial<CampaignConfig>) =>
+      program.methods.validateConfig(target, { ...defaultConfig(), ...config }).rpc();
+
+    await validate({});
+    await expectError(validate({ feeBps: 10_001 }), /InvalidFee/i);
+    await expectError(
+      validate({ deadline: new anchor.BN(Math.floor(Date.now() / 1000) - 60) }),
+      /InvalidDeadline/i
+    );
+    await expectError(
+      validate({ minFinalizeAmount: new anchor.BN(1_000_001) }),
+      /InvalidMinFinalizeAmount/i
+    );
+    await expectError(
+      program.methods.validateConfig(new anchor.BN(0), defaultConfig()).rpc(),
+      /InvalidAmount/i
+    );
+  });
+
+  it("max_active_per_creator caps open campaigns until one closes", async () => {
+    const profilePda = findCreatorProfilePda(creator.publicKey);
+    const open = () =>
+      initCampaign({
+        creator,
+        beneficiary: Keypair.generate().publicKey,
+        targetLamports: 1_000_000,
+      });
+    // Make sure the profile exists, then allow exactly one more campaign
+    await open();
+    const { activeCampaigns } = await program.account.creatorProfile.fetch(profilePda);
+
+    await setMaxActivePerCreator(activeCampaigns + 1);
+    try {
+      const { campaignPda } = await open();
+      assert.equal(
+        (await program.account.creatorProfile.fetch(profilePda)).activeCampaigns,
+        activeCampaigns + 1
+      );
+      await expectError(open(), /TooManyActiveCampaigns/i);
+
+      await cancelCampaign(creator, campaignPda);
+      assert.equal(
+        (await program.account.creatorProfile.fetch(profilePda)).activeCampaigns,
+        activeCampaigns
+      );
+      await open();
+    } finally {
+      await setMaxActivePerCreator(0);
+    }
+  });
+
+  it("END_ACTION_PAUSE pauses deposits at the deadline until extend_deadline", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const deadline = Math.floor(Date.now() / 1000) + 3;
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+      config: { deadline: new anchor.BN(deadline), endAction: END_ACTION_PAUSE },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 200_000);
+    await expectError(
+      extendDeadline(creator, campaignPda, deadline + 60),
+      /CampaignNotPaused/i
+    );
+
+    await waitForUnixTime(deadline);
+    await processDeadline(campaignPda, beneficiary.publicKey, vaultLamportsPda);
+    let campaign = await program.account.campaign.fetch(campaignPda);
+    assert.isTrue(campaign.isPaused);
+    await expectError(
+      depositFrom(donor, campaignPda, vaultLamportsPda, 100_000),
+      /CampaignPaused/i
+    );
+    await expectError(markFailed(campaignPda), /CampaignStillOpen/i);
+
+    // Running the crank again changes nothing
+    await processDeadline(campaignPda, beneficiary.publicKey, vaultLamportsPda);
+    await expectError(
+      extendDeadline(creator, campaignPda, Math.floor(Date.now() / 1000) - 10),
+      /InvalidDeadline/i
+    );
+
+    const newDeadline = Math.floor(Date.now() / 1000) + 60;
+    await extendDeadline(creator, campaignPda, newDeadline);
+    campaign = await program.account.campaign.fetch(campaignPda);
+    assert.isFalse(campaign.isPaused);
+    assert.isFalse(campaign.deadlineProcessed);
+    assert.equal(campaign.deadline.toNumber(), newDeadline);
+
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 100_000);
+    campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), "300000");
+  });
+
+  it("END_ACTION_ALLOW_FINALIZE_ONLY blocks deposits and failure but not finalize", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const deadline = Math.floor(Date.now() / 1000) + 3;
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+      config: { deadline: new anchor.BN(deadline), endAction: END_ACTION_ALLOW_FINALIZE_ONLY },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 300_000);
+
+    await waitForUnixTime(deadline);
+    await processDeadline(campaignPda, beneficiary.publicKey, vaultLamportsPda);
+    let campaign = await program.account.campaign.fetch(campaignPda);
+    assert.isTrue(campaign.deadlineProcessed);
+    assert.isFalse(campaign.isPaused);
+    assert.isFalse(campaign.isFinalized);
+
+    await expectError(
+      depositFrom(donor, campaignPda, vaultLamportsPda, 100_000),
+      /DeadlinePassed/i
+    );
+    await expectError(markFailed(campaignPda), /CampaignStillOpen/i);
+
+    await finalizeBy(beneficiary, creator.publicKey, campaignPda, vaultLamportsPda);
+    campaign = await program.account.campaign.fetch(campaignPda);
+    assert.isTrue(campaign.isFinalized);
+    assert.equal(campaign.finalFunds.toString(), "300000");
+  });
+
+  it("receipt numbers follow the campaign deposit count and stick to the first deposit", async () => {
+    const first = Keypair.generate();
+    const second = Keypair.generate();
+    await airdrop(first.publicKey);
+    await airdrop(second.publicKey);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+    });
+    const receipt = async (who: PublicKey) =>
+      (
+        await program.methods
+          .getReceiptNumber()
+          .accounts({
+            campaign: campaignPda,
+            donor: who,
+            contribution: findContributionPda(campaignPda, who),
+          })
+          .view()
+      ).toNumber();
+
+    assert.equal(await receipt(first.publicKey), 0);
+    await depositFrom(first, campaignPda, vaultLamportsPda, 100_000);
+    await depositFrom(second, campaignPda, vaultLamportsPda, 100_000);
+
+    // deposit returns the receipt; a repeat deposit keeps the original number
+    const simulated = await program.methods
+      .deposit(new anchor.BN(100_000))
+      .accounts({
+        donor: first.publicKey,
+        campaign: campaignPda,
+        beneficiary: await campaignBeneficiary(campaignPda),
+        vaultLamports: vaultLamportsPda,
+        contribution: findContributionPda(campaignPda, first.publicKey),
+        socialAttestation: await socialAttestationFor(campaignPda, first.publicKey),
+        donorProfile: findDonorProfilePda(first.publicKey),
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([first])
+      .simulate();
+    const prefix = `Program return: ${program.programId.toBase58()} `;
+    const returned = simulated.raw.find((line) => line.startsWith(prefix));
+    assert.isDefined(returned);
+    assert.equal(
+      Buffer.from(returned!.slice(prefix.length), "base64").readBigUInt64LE().toString(),
+      "1"
+    );
+
+    await depositFrom(first, campaignPda, vaultLamportsPda, 100_000);
+    assert.equal(await receipt(first.publicKey), 1);
+    assert.equal(await receipt(second.publicKey), 2);
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.depositCount, 3);
+  });
+
+  it("require_social takes deposits only from donors attested by the trusted issuer", async () => {
+    const issuer = Keypair.generate();
+    const donor = Keypair.generate();
+    const expired = Keypair.generate();
+    await airdrop(issuer.publicKey);
+    await airdrop(donor.publicKey);
+    await airdrop(expired.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+      config: { requireSocial: true, socialIssuer: issuer.publicKey },
+    });
+    await expectError(
+      depositFrom(donor, campaignPda, vaultLamportsPda, 100_000),
+      /SocialRequired/i
+    );
+
+    await issueSocialAttestation(issuer, donor.publicKey, 0);
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 100_000);
+
+    await issueSocialAttestation(issuer, expired.publicKey, Math.floor(Date.now() / 1000) - 60);
+    await expectError(
+      depositFrom(expired, campaignPda, vaultLamportsPda, 100_000),
+      /SocialRequired/i
+    );
+
+    // Paths without the attestation account are closed on gated campaigns
+    await fundVoucherEscrow(donor, 100_000);
+    await expectError(
+      depositVoucher(donor, donor.publicKey, campaignPda, vaultLamportsPda, 100_000, 1),
+      /SocialRequired/i
+    );
+
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), "100000");
+  });
+
+  it("initialize rejects require_social without an issuer", async () => {
+    await expectError(
+      initCampaign({
+        creator,
+        beneficiary: Keypair.generate().publicKey,
+        targetLamports: 1_000_000,
+        config: { requireSocial: true },
+      }),
+      /InvalidSocialConfig/i
+    );
+  });
+
+  it("initialize rejects require_social with a large deposit threshold", async () => {
+    await expectError(
+      initCampaign({
+        creator,
+        beneficiary: Keypair.generate().publicKey,
+        targetLamports: 1_000_000,
+        config: {
+          requireSocial: true,
+          socialIssuer: Keypair.generate().publicKey,
+          largeDepositThreshold: new anchor.BN(500_000),
+        },
+      }),
+      /LargeDepositsUnconfirmable/i
+    );
+  });
+
+  it("finalize_and_cleanup finalizes, then closes contribution accounts page by page", async () => {
+    const beneficiary = Keypair.generate();
+    const donors = [Keypair.generate(), Keypair.generate(), Keypair.generate()];
+    for (const donor of donors) {
+      await airdrop(donor.publicKey);
+    }
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 600_000,
+    });
+    for (const donor of donors) {
+      await depositFrom(donor, campaignPda, vaultLamportsPda, 200_000);
+    }
+    const [a, b, c] = donors.map((donor) => findContributionPda(campaignPda, donor.publicKey));
+
+    await finalizeAndCleanup(beneficiary, campaignPda, vaultLamportsPda, [a, b]);
+    let campaign = await program.account.campaign.fetch(campaignPda);
+    assert.isTrue(campaign.isFinalized);
+    assert.equal(campaign.finalFunds.toString(), "600000");
+    assert.equal(campaign.contributionAccounts, 3);
+    assert.equal(campaign.childrenClosed, 2);
+    assert.isFalse(campaign.fullyCleaned);
+    assert.isNull(await provider.connection.getAccountInfo(a));
+    assert.isNull(await provider.connection.getAccountInfo(b));
+
+    // The authority pays the fees and gets the last contribution's rent
+    const rent = (await provider.connection.getAccountInfo(c))!.lamports;
+    const authorityBefore = await provider.connection.getBalance(creator.publicKey);
+    await finalizeAndCleanup(beneficiary, campaignPda, vaultLamportsPda, [c]);
+    const authorityAfter = await provider.connection.getBalance(creator.publicKey);
+    assert.isAbove(authorityAfter - authorityBefore, rent - 20_000);
+
+    campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.childrenClosed, 3);
+    assert.isTrue(campaign.fullyCleaned);
+    await expectError(
+      finalizeAndCleanup(beneficiary, campaignPda, vaultLamportsPda, [a]),
+      /InvalidReceipt/i
+    );
+  });
+
+  it("net_to_beneficiary matches what finalize pays and ignores dust sweeps", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+      config: { feeBps: 250, minFinalizeAmount: new anchor.BN(10_000) },
+    });
+    const net = async () =>
+      (await program.methods.netToBeneficiary().accounts({ campaign: campaignPda }).view()).toNumber();
+
+    // Below min_finalize_amount the whole balance would be swept to the authority
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 5_000);
+    assert.equal(await net(), 0);
+
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 395_000);
+    assert.equal(await net(), 390_000);
+
+    const before = await provider.connection.getBalance(beneficiary.publicKey);
+    await finalizeBy(beneficiary, creator.publicKey, campaignPda, vaultLamportsPda);
+    const after = await provider.connection.getBalance(beneficiary.publicKey);
+    assert.equal(after - before, 390_000);
+    assert.equal(await net(), 0);
+  });
+
+  it("creator bond is escrowed at initialize and returned after finalize", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 500_000,
+      config: { bondAmount: new anchor.BN(1_000_000) },
+    });
+    const bondPda = findCreatorBondPda(campaignPda);
+    const rent = await provider.connection.getMinimumBalanceForRentExemption(0);
+    assert.equal(await provider.connection.getBalance(bondPda), 1_000_000 + rent);
+    let campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.bondAmount.toString(), "1000000");
+    assert.equal(campaign.bondState, 1);
+
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 500_000);
+    await expectError(claimBond(campaignPda), /BondNotReturnable/i);
+
+    await finalizeBy(beneficiary, creator.publicKey, campaignPda, vaultLamportsPda);
+    const before = await provider.connection.getBalance(creator.publicKey);
+    await claimBond(campaignPda);
+    const after = await provider.connection.getBalance(creator.publicKey);
+    assert.isAbove(after - before, 1_000_000 + rent - 20_000);
+    assert.equal(await provider.connection.getBalance(bondPda), 0);
+    campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.bondState, 2);
+    await expectError(claimBond(campaignPda), /BondNotPosted/i);
+  });
+
+  it("cancelling with donors forfeits the bond, paged out pro-rata before refunds", async () => {
+    const donor = Keypair.generate();
+    const other = Keypair.generate();
+    const cranker = Keypair.generate();
+    await airdrop(donor.publicKey);
+    await airdrop(other.publicKey);
+    await airdrop(cranker.publicKey);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+      config: { bondAmount: new anchor.BN(400_000) },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 300_000);
+    await depositFrom(other, campaignPda, vaultLamportsPda, 100_000);
+
+    await cancelCampaign(creator, campaignPda);
+    let campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.bondState, 3);
+    assert.equal(campaign.bondBasis.toString(), "400000");
+    await expectError(refundFrom(donor, campaignPda, vaultLamportsPda), /BondDistributionInProgress/i);
+    await expectError(claimBond(campaignPda), /BondNotPosted/i);
+
+    // Anyone can page out a bond forfeited by cancel; the last donor takes the rent
+    const rent = await provider.connection.getMinimumBalanceForRentExemption(0);
+    const donorBefore = await provider.connection.getBalance(donor.publicKey);
+    const otherBefore = await provider.connection.getBalance(other.publicKey);
+    await slashBond(cranker, campaignPda, [donor.publicKey]);
+    await slashBond(cranker, campaignPda, [donor.publicKey, other.publicKey]);
+    assert.equal((await provider.connection.getBalance(donor.publicKey)) - donorBefore, 300_000);
+    assert.equal((await provider.connection.getBalance(other.publicKey)) - otherBefore, 100_000 + rent);
+
+    campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.bondState, 4);
+    assert.equal(campaign.bondProcessed, 2);
+    await refundFrom(donor, campaignPda, vaultLamportsPda);
+  });
+
+  it("slash_bond needs the arbiter or admin and a failed campaign", async () => {
+    const arbiter = Keypair.generate();
+    const donor = Keypair.generate();
+    const stranger = Keypair.generate();
+    await airdrop(arbiter.publicKey);
+    await airdrop(donor.publicKey);
+    await airdrop(stranger.publicKey);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+      config: { bondAmount: new anchor.BN(200_000), arbiter: arbiter.publicKey },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 100_000);
+
+    await expectError(slashBond(arbiter, campaignPda, [donor.publicKey]), /BondNotSlashable/i);
+    await raiseDispute(donor, campaignPda);
+    await resolveDispute(arbiter, campaignPda, true);
+    await expectError(slashBond(stranger, campaignPda, [donor.publicKey]), /Unauthorized/i);
+
+    const before = await provider.connection.getBalance(donor.publicKey);
+    await slashBond(arbiter, campaignPda, [donor.publicKey]);
+    const rent = await provider.connection.getMinimumBalanceForRentExemption(0);
+    assert.equal((await provider.connection.getBalance(donor.publicKey)) - before, 200_000 + rent);
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.bondState, 4);
+  });
+
+  it("donor profile aggregates deposits and refunds across campaigns", async () => {
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+    assert.equal((await donorReputation(donor.publicKey)).toString(), "0");
+
+    const first = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+    });
+    const second = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+    });
+    await depositFrom(donor, first.campaignPda, first.vaultLamportsPda, 200_000);
+    await depositFrom(donor, first.campaignPda, first.vaultLamportsPda, 100_000);
+    await depositFrom(donor, second.campaignPda, second.vaultLamportsPda, 200_000);
+    await cancelCampaign(creator, second.campaignPda);
+    await refundFrom(donor, second.campaignPda, second.vaultLamportsPda);
+
+    const profile = await program.account.donorProfile.fetch(findDonorProfilePda(donor.publicKey));
+    assert.isTrue(profile.donor.equals(donor.publicKey));
+    assert.equal(profile.totalContributed.toString(), "500000");
+    assert.equal(profile.campaignsBacked, 2);
+    assert.equal(profile.totalRefunded.toString(), "200000");
+    assert.equal((await donorReputation(donor.publicKey)).toString(), "500000");
+  });
+
+  it("min_slot_gap spaces out a donor's deposits by slots", async () => {
+    const donor = Keypair.generate();
+    const other = Keypair.generate();
+    await airdrop(donor.publicKey);
+    await airdrop(other.publicKey);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+      config: { minSlotGap: new anchor.BN(5) },
+    });
+
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 100_000);
+    const contribution = await program.account.contribution.fetch(
+      findContributionPda(campaignPda, donor.publicKey)
+    );
+    assert.isAbove(contribution.lastDepositSlot.toNumber(), 0);
+    await expectError(
+      depositFrom(donor, campaignPda, vaultLamportsPda, 100_000),
+      /DonationTooSoon/i
+    );
+    // The gap is per donor
+    await depositFrom(other, campaignPda, vaultLamportsPda, 100_000);
+
+    await waitForSlot(contribution.lastDepositSlot.toNumber() + 5);
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 100_000);
+    let campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), "300000");
+
+    // Anonymous deposits are gapped per commitment
+    const commitment = donorCommitment(donor.publicKey, randomBytes(32));
+    await depositAnonymous(donor, campaignPda, vaultLamportsPda, 100_000, commitment);
+    await expectError(
+      depositAnonymous(donor, campaignPda, vaultLamportsPda, 100_000, commitment),
+      /DonationTooSoon/i
+    );
+    campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), "400000");
+  });
+
+  it("contributions_of batch-reads amounts and zeroes anything that is not a contribution", async () => {
+    const donor = Keypair.generate();
+    const other = Keypair.generate();
+    await airdrop(donor.publicKey);
+    await airdrop(other.publicKey);
+    const first = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+    });
+    const second = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+    });
+    await depositFrom(donor, first.campaignPda, first.vaultLamportsPda, 300_000);
+    await depositFrom(other, first.campaignPda, first.vaultLamportsPda, 100_000);
+    await depositFrom(donor, second.campaignPda, second.vaultLamportsPda, 50_000);
+
+    const accounts = [
+      findContributionPda(first.campaignPda, donor.publicKey),
+      first.campaignPda,
+      findContributionPda(second.campaignPda, donor.publicKey),
+      findContributionPda(first.campaignPda, Keypair.generate().publicKey),
+      findContributionPda(first.campaignPda, other.publicKey),
+    ];
+    const amounts: anchor.BN[] = await program.methods
+      .contributionsOf()
+      .accounts({ campaign: first.campaignPda })
+      .remainingAccounts(accounts.map((pubkey) => ({ pubkey, isWritable: false, isSigner: false })))
+      .view();
+    assert.deepEqual(
+      amounts.map((amount) => amount.toString()),
+      ["300000", "0", "0", "0", "100000"]
+    );
+  });
+
+  it("crank_finalize tips the cranker with the vault rent excess under cranker_reward", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    const cranker = Keypair.generate();
+    await airdrop(donor.publicKey);
+    await airdrop(cranker.publicKey);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 500_000,
+      config: { crankerReward: true },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 200_000);
+    await expectError(crankFinalize(cranker, campaignPda), /TargetNotReached/i);
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 300_000);
+
+    const config = await program.account.protocolConfig.fetchNullable(findProtocolConfigPda());
+    const splitBps = config ? config.rentSplitBps : 0;
+    const excess = (await provider.connection.getBalance(vaultLamportsPda)) - 500_000;
+    const tip = excess - Math.floor((excess * splitBps) / 10_000);
+    const crankerBefore = await provider.connection.getBalance(cranker.publicKey);
+    const beneficiaryBefore = await provider.connection.getBalance(beneficiary.publicKey);
+    await crankFinalize(cranker, campaignPda);
+
+    assert.equal(
+      (await provider.connection.getBalance(beneficiary.publicKey)) - beneficiaryBefore,
+      500_000
+    );
+    assert.equal((await provider.connection.getBalance(cranker.publicKey)) - crankerBefore, tip);
+    assert.equal(await provider.connection.getBalance(vaultLamportsPda), 0);
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.isTrue(campaign.isFinalized);
+  });
+
+  it("crank_finalize without cranker_reward leaves the remainder with the authority", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    const cranker = Keypair.generate();
+    await airdrop(donor.publicKey);
+    await airdrop(cranker.publicKey);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 500_000,
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 500_000);
+
+    const crankerBefore = await provider.connection.getBalance(cranker.publicKey);
+    await crankFinalize(cranker, campaignPda);
+    assert.equal(await provider.connection.getBalance(cranker.publicKey), crankerBefore);
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.isTrue(campaign.isFinalized);
+    await expectError(crankFinalize(cranker, campaignPda), /CampaignFinalized/i);
+  });
+
+  it("full_state returns the versioned, length-prefixed campaign", async () => {
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+      config: { feeBps: 300 },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 250_000);
+
+    const blob: Buffer = await program.methods
+      .fullState()
+      .accounts({ campaign: campaignPda })
+      .view();
+    assert.equal(blob[0], 1);
+    const len = blob.readUInt16LE(1);
+    assert.equal(blob.length, 3 + len);
+
+    // The payload is exactly the account data after the discriminator
+    const info = await provider.connection.getAccountInfo(campaignPda);
+    assert.isTrue(blob.subarray(3).equals(info!.data.subarray(8, 8 + len)));
+    const decoded = program.coder.accounts.decode(
+      "campaign",
+      Buffer.concat([info!.data.subarray(0, 8), blob.subarray(3)])
+    );
+    assert.equal(decoded.funds.toString(), "250000");
+    assert.equal(decoded.feeBps, 300);
+  });
+
+  it("an unrevealed anonymous deposit does not hold up a forfeited bond payout", async () => {
+    const donor = Keypair.generate();
+    const anonymous = Keypair.generate();
+    await airdrop(donor.publicKey);
+    await airdrop(anonymous.publicKey);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+      config: { bondAmount: new anchor.BN(100_000) },
+    });
+    const commitment = donorCommitment(anonymous.publicKey, randomBytes(32));
+    await depositAnonymous(anonymous, campaignPda, vaultLamportsPda, 1, commitment);
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 200_000);
+    let campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.donorCount, 1);
+
+    await cancelCampaign(creator, campaignPda);
+    await slashBond(donor, campaignPda, [donor.publicKey]);
+    campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.bondState, 4);
+    await refundFrom(donor, campaignPda, vaultLamportsPda);
+  });
+
+  it("reward campaigns only finalize with the mint and archive once rewards are claimed", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+    await airdrop(donor.publicKey);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 500_000,
+      config: { rewardRate: new anchor.BN(2) },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 500_000);
+
+    await expectError(
+      finalizeBy(beneficiary, creator.publicKey, campaignPda, vaultLamportsPda),
+      /RewardsRequireMint/i
+    );
+    await expectError(crankFinalize(donor, campaignPda), /RewardsRequireMint/i);
+    await finalizeAndReward(beneficiary, campaignPda, vaultLamportsPda);
+
+    const contribution = findContributionPda(campaignPda, donor.publicKey);
+    const archive = () =>
+      program.methods
+        .archive()
+        .accounts({
+          authority: creator.publicKey,
+          campaign: campaignPda,
+          archivedCampaign: findArchivedCampaignPda(campaignPda),
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+    // The unclaimed contribution is skipped, so the campaign cannot be archived yet
+    await finalizeAndCleanup(beneficiary, campaignPda, vaultLamportsPda, [contribution]);
+    assert.isNotNull(await provider.connection.getAccountInfo(contribution));
+    await expectError(archive(), /CampaignNotCleaned/i);
+
+    const destination = await createTokenAccount(findRewardMintPda(campaignPda), donor.publicKey);
+    await claimReward(donor, campaignPda, destination);
+    await finalizeAndCleanup(beneficiary, campaignPda, vaultLamportsPda, [contribution]);
+    await archive();
+  });
+
+  it("archive waits until the creator bond is claimed", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 500_000,
+      config: { bondAmount: new anchor.BN(1_000_000) },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 500_000);
+    await finalizeAndCleanup(beneficiary, campaignPda, vaultLamportsPda, [
+      findContributionPda(campaignPda, donor.publicKey),
+    ]);
+
+    const archive = () =>
+      program.methods
+        .archive()
+        .accounts({
+          authority: creator.publicKey,
+          campaign: campaignPda,
+          archivedCampaign: findArchivedCampaignPda(campaignPda),
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    await expectError(archive(), /BondOutstanding/i);
+
+    await claimBond(campaignPda);
+    await archive();
+    assert.isNull(await provider.connection.getAccountInfo(campaignPda));
+  });
+
+  it("lowering the target lets distribute_overflow pay donors pro-rata each round", async () => {
+    const beneficiary = Keypair.generate();
+    const a = Keypair.generate();
+    const b = Keypair.generate();
+    await airdrop(a.publicKey);
+    await airdrop(b.publicKey);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+    });
+    await depositFrom(a, campaignPda, vaultLamportsPda, 600_000);
+    await depositFrom(b, campaignPda, vaultLamportsPda, 400_000);
+    await updateTarget(campaignPda, 500_000);
+
+    const balance = (key: PublicKey) => provider.connection.getBalance(key);
+    const contributionOf = (donor: Keypair) =>
+      program.account.contribution.fetch(findContributionPda(campaignPda, donor.publicKey));
+
+    let aBefore = await balance(a.publicKey);
+    let bBefore = await balance(b.publicKey);
+    await distributeOverflow(campaignPda, beneficiary.publicKey, vaultLamportsPda, [a.publicKey]);
+    assert.equal((await balance(a.publicKey)) - aBefore, 300_000);
+    await expectError(cancelCampaign(creator, campaignPda), /OverflowDistributionInProgress/i);
+    await expectError(updateTarget(campaignPda, 400_000), /OverflowDistributionInProgress/i);
+
+    await distributeOverflow(campaignPda, beneficiary.publicKey, vaultLamportsPda, [b.publicKey]);
+    assert.equal((await balance(b.publicKey)) - bBefore, 200_000);
+    let campaign = await program.account.campaign.fetch(campaignPda);
+    assert.isFalse(campaign.overflowInProgress);
+    assert.equal(campaign.overflowRound, 1);
+    assert.equal(campaign.funds.toString(), "500000");
+    assert.equal((await contributionOf(a)).amount.toString(), "300000");
+    assert.equal((await contributionOf(b)).amount.toString(), "200000");
+
+    // A second round pays every donor again
+    await updateTarget(campaignPda, 400_000);
+    aBefore = await balance(a.publicKey);
+    bBefore = await balance(b.publicKey);
+    await distributeOverflow(campaignPda, beneficiary.publicKey, vaultLamportsPda, [
+      a.publicKey,
+      b.publicKey,
+    ]);
+    assert.equal((await balance(a.publicKey)) - aBefore, 60_000);
+    assert.equal((await balance(b.publicKey)) - bBefore, 40_000);
+    campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.overflowRound, 2);
+    assert.equal(campaign.funds.toString(), "400000");
+    assert.equal((await contributionOf(a)).overflowRound, 2);
+    assert.equal((await contributionOf(b)).overflowRound, 2);
+  });
+
+  it("overflow shares below dust_refund_threshold go to the beneficiary", async () => {
+    const beneficiary = Keypair.generate();
+    const a = Keypair.generate();
+    const b = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+    await airdrop(a.publicKey);
+    await airdrop(b.publicKey);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+      config: { dustRefundThreshold: new anchor.BN(50_000) },
+    });
+    await depositFrom(a, campaignPda, vaultLamportsPda, 900_000);
+    await depositFrom(b, campaignPda, vaultLamportsPda, 100_000);
+    await updateTarget(campaignPda, 900_000);
+
+    const balance = (key: PublicKey) => provider.connection.getBalance(key);
+    const aBefore = await balance(a.publicKey);
+    const bBefore = await balance(b.publicKey);
+    const beneficiaryBefore = await balance(beneficiary.publicKey);
+    await distributeOverflow(campaignPda, beneficiary.publicKey, vaultLamportsPda, [
+      a.publicKey,
+      b.publicKey,
+    ]);
+
+    // a's 90_000 share clears the threshold, b's 10_000 does not
+    assert.equal((await balance(a.publicKey)) - aBefore, 90_000);
+    assert.equal((await balance(b.publicKey)) - bBefore, 0);
+    assert.equal((await balance(beneficiary.publicKey)) - beneficiaryBefore, 10_000);
+
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.overflowDustRedirected.toString(), "10000");
+    assert.equal(campaign.funds.toString(), "900000");
+    const contributionOf = (donor: Keypair) =>
+      program.account.contribution.fetch(findContributionPda(campaignPda, donor.publicKey));
+    assert.equal((await contributionOf(a)).amount.toString(), "810000");
+    assert.equal((await contributionOf(b)).amount.toString(), "90000");
+  });
+
+  it("a contribution created by reveal keeps the anonymous deposit's receipt number", async () => {
+    const donor = Keypair.generate();
+    const other = Keypair.generate();
+    await airdrop(donor.publicKey);
+    await airdrop(other.publicKey);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+    });
+
+    await depositFrom(other, campaignPda, vaultLamportsPda, 100_000);
+    const salt = randomBytes(32);
+    const commitment = donorCommitment(donor.publicKey, salt);
+    await depositAnonymous(donor, campaignPda, vaultLamportsPda, 100_000, commitment);
+    await depositFrom(other, campaignPda, vaultLamportsPda, 100_000);
+    await depositAnonymous(donor, campaignPda, vaultLamportsPda, 100_000, commitment);
+    await revealContribution(donor, campaignPda, commitment, salt);
+
+    const contribution = await program.account.contribution.fetch(
+      findContributionPda(campaignPda, donor.publicKey)
+    );
+    assert.equal(contribution.amount.toString(), "200000");
+    assert.equal(contribution.receiptNumber.toString(), "2");
+  });
+
+  it("donor profile books every path into a named contribution", async () => {
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+    const profilePda = findDonorProfilePda(donor.publicKey);
+    const daily = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+    });
+    const large = await initCampaign({
       creator,
-      beneficiary: beneficiary.publicKey,
-      targetLamports,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 2_000_000,
+      config: { largeDepositThreshold: new anchor.BN(500_000) },
     });
 
+    await depositFrom(donor, daily.campaignPda, daily.vaultLamportsPda, 200_000);
+    const salt = randomBytes(32);
+    const commitment = donorCommitment(donor.publicKey, salt);
+    await depositAnonymous(donor, daily.campaignPda, daily.vaultLamportsPda, 100_000, commitment);
+    // An unrevealed deposit is not the donor's yet
+    let profile = await program.account.donorProfile.fetch(profilePda);
+    assert.equal(profile.totalContributed.toString(), "200000");
+    await revealContribution(donor, daily.campaignPda, commitment, salt);
+
+    const pendingPda = findPendingDepositPda(large.campaignPda, donor.publicKey);
     await program.methods
-      .deposit(new anchor.BN(depositLamports))
+      .requestLargeDeposit(new anchor.BN(600_000))
       .accounts({
-        donor: creator.publicKey,
-        campaign: campaignPda,
-        vaultLamports: vaultLamportsPda,
+        donor: donor.publicKey,
+        campaign: large.campaignPda,
+        pendingDeposit: pendingPda,
         systemProgram: SystemProgram.programId,
       })
-      .rpc();
-
-    const beneficiaryBalanceBefore = await provider.connection.getBalance(
-      beneficiary.publicKey
-    );
-
+      .signers([donor])
+      .rpc({ commitment: "confirmed" });
+    const pending = await program.account.pendingDeposit.fetch(pendingPda);
+    await waitForSlot(pending.requestedSlot.toNumber() + 1);
     await program.methods
-      .finalize()
+      .confirmLargeDeposit()
       .accounts({
-        beneficiary: beneficiary.publicKey,
-        authority: creator.publicKey,
-        campaign: campaignPda,
-        vaultLamports: vaultLamportsPda,
+        donor: donor.publicKey,
+        campaign: large.campaignPda,
+        vaultLamports: large.vaultLamportsPda,
+        pendingDeposit: pendingPda,
+        contribution: findContributionPda(large.campaignPda, donor.publicKey),
+        donorProfile: profilePda,
         systemProgram: SystemProgram.programId,
       })
-      .signers([beneficiary])
+      .signers([donor])
       .rpc();
 
+    profile = await program.account.donorProfile.fetch(profilePda);
+    assert.equal(profile.totalContributed.toString(), "900000");
+    assert.equal(profile.campaignsBacked, 2);
+
+    // The refund takes back the deposit and the revealed lamports, both booked above
+    await cancelCampaign(creator, daily.campaignPda);
+    await refundFrom(donor, daily.campaignPda, daily.vaultLamportsPda);
+    profile = await program.account.donorProfile.fetch(profilePda);
+    assert.equal(profile.totalRefunded.toString(), "300000");
+  });
+
+  it("crank_finalize pays no cranker tip on a dust sweep", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    const cranker = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+    await airdrop(donor.publicKey);
+    await airdrop(cranker.publicKey);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+      config: {
+        crankerReward: true,
+        allowPartialWithdrawals: true,
+        minFinalizeAmount: new anchor.BN(200_000),
+      },
+    });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 1_000_000);
+    await withdrawBy(beneficiary, campaignPda, vaultLamportsPda, 900_000);
+
+    // raised counts the withdrawal, so the crank goes through as a dust sweep
+    const crankerBefore = await provider.connection.getBalance(cranker.publicKey);
+    await crankFinalize(cranker, campaignPda);
+    assert.equal(await provider.connection.getBalance(cranker.publicKey), crankerBefore);
+    assert.equal(await provider.connection.getBalance(vaultLamportsPda), 0);
     const campaign = await program.account.campaign.fetch(campaignPda);
-    assert.equal(campaign.isFinalized, true);
-    assert.equal(campaign.funds.toString(), "0");
+    assert.isTrue(campaign.isFinalized);
+    assert.equal(campaign.finalFunds.toString(), "100000");
+  });
 
-    const beneficiaryBalanceAfter = await provider.connection.getBalance(
-      beneficiary.publicKey
-    );
-    const delta = beneficiaryBalanceAfter - beneficiaryBalanceBefore;
-    const feeBuffer = 20_000;
-    assert.ok(
-      delta >= depositLamports - feeBuffer,
-      "beneficiary balance should increase by deposited funds minus fees"
-    );
+  it("the treasury rent split leaves the unused match pool out", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    const treasury = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+    await airdrop(donor.publicKey);
+    await airdrop(treasury.publicKey);
 
-    const vaultLamportsAfter = await provider.connection.getBalance(
-      vaultLamportsPda
-    );
-    assert.equal(vaultLamportsAfter, 0);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+    });
+    await fundMatch(creator, campaignPda, vaultLamportsPda, 300_000);
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 500_000);
+    const remainder = (await provider.connection.getBalance(vaultLamportsPda)) - 500_000 - 300_000;
+
+    await setRentSplit(treasury.publicKey, 2_500);
+    try {
+      const before = await provider.connection.getBalance(treasury.publicKey);
+      await finalizeBy(beneficiary, creator.publicKey, campaignPda, vaultLamportsPda);
+      const after = await provider.connection.getBalance(treasury.publicKey);
+      assert.equal(after - before, Math.floor((remainder * 2_500) / 10_000));
+      assert.equal(await provider.connection.getBalance(vaultLamportsPda), 0);
+    } finally {
+      await setRentSplit(creator.publicKey, 0);
+    }
   });
 
-  it("finalize rejects unauthorized caller", async () => {
+  it("partial withdrawals rule out cancel and refunds", async () => {
     const beneficiary = Keypair.generate();
-    const unauthorized = Keypair.generate();
+    const a = Keypair.generate();
+    const b = Keypair.generate();
     await airdrop(beneficiary.publicKey);
-    await airdrop(unauthorized.publicKey);
+    await airdrop(a.publicKey);
+    await airdrop(b.publicKey);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+      config: {
+        allowPartialWithdrawals: true,
+        refundPolicy: { ...defaultRefundPolicy(), refundsEnabled: true },
+      },
+    });
+    await depositFrom(a, campaignPda, vaultLamportsPda, 100_000);
+    await depositFrom(b, campaignPda, vaultLamportsPda, 100_000);
+    await withdrawBy(beneficiary, campaignPda, vaultLamportsPda, 150_000);
 
-    const targetLamports = Math.floor(0.05 * LAMPORTS_PER_SOL);
-    const depositLamports = Math.floor(0.01 * LAMPORTS_PER_SOL);
+    await expectError(cancelCampaign(creator, campaignPda), /FundsWithdrawn/i);
+    await expectError(refundFrom(a, campaignPda, vaultLamportsPda), /FundsWithdrawn/i);
+
+    // The rest can still be paid out
+    await finalizeBy(beneficiary, creator.publicKey, campaignPda, vaultLamportsPda);
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.isTrue(campaign.isFinalized);
+    assert.equal(campaign.finalFunds.toString(), "50000");
+  });
+
+  it("an auto-finalize campaign short of its target fails whichever crank lands first", async () => {
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+
+    const deadline = Math.floor(Date.now() / 1000) + 4;
+    const config = { deadline: new anchor.BN(deadline), endAction: END_ACTION_AUTO_FINALIZE };
+    const deadlineFirst = Keypair.generate();
+    const first = await initCampaign({
+      creator,
+      beneficiary: deadlineFirst.publicKey,
+      targetLamports: 1_000_000,
+      config,
+    });
+    const failedFirst = Keypair.generate();
+    const second = await initCampaign({
+      creator,
+      beneficiary: failedFirst.publicKey,
+      targetLamports: 1_000_000,
+      config,
+    });
+    await depositFrom(donor, first.campaignPda, first.vaultLamportsPda, 600_000);
+    await depositFrom(donor, second.campaignPda, second.vaultLamportsPda, 600_000);
+    await waitForUnixTime(deadline);
+
+    // process_deadline first: it fails the campaign, mark_failed has nothing left to do
+    await processDeadline(first.campaignPda, deadlineFirst.publicKey, first.vaultLamportsPda);
+    assert.equal(await provider.connection.getBalance(deadlineFirst.publicKey), 0);
+    await expectError(markFailed(first.campaignPda), /CampaignFailed/i);
 
+    // mark_failed first: process_deadline is then a no-op
+    await markFailed(second.campaignPda);
+    await processDeadline(second.campaignPda, failedFirst.publicKey, second.vaultLamportsPda);
+    assert.equal(await provider.connection.getBalance(failedFirst.publicKey), 0);
+
+    for (const { campaignPda, vaultLamportsPda } of [first, second]) {
+      const campaign = await program.account.campaign.fetch(campaignPda);
+      assert.isTrue(campaign.isFailed);
+      assert.isFalse(campaign.isFinalized);
+      await refundFrom(donor, campaignPda, vaultLamportsPda);
+      const contribution = await program.account.contribution.fetch(
+        findContributionPda(campaignPda, donor.publicKey)
+      );
+      assert.equal(contribution.amount.toString(), "0");
+    }
+  });
+
+  it("finalize_to_backup needs a whitelisted backup while the whitelist is enforced", async () => {
+    const beneficiary = Keypair.generate();
+    const backup = Keypair.generate();
+    const donor = Keypair.generate();
+    await airdrop(backup.publicKey);
+    await airdrop(donor.publicKey);
+    const protocolConfig = await ensureProtocolConfig();
+
+    const deadline = Math.floor(Date.now() / 1000) + 4;
     const { campaignPda, vaultLamportsPda } = await initCampaign({
       creator,
       beneficiary: beneficiary.publicKey,
-      targetLamports,
+      targetLamports: 1_000_000,
+      config: {
+        ...defaultConfig(),
+        deadline: new anchor.BN(deadline),
+        backupBeneficiary: backup.publicKey,
+        backupDelay: new anchor.BN(0),
+      },
     });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 400_000);
+    await waitForUnixTime(deadline);
 
-    await program.methods
-      .deposit(new anchor.BN(depositLamports))
-      .accounts({
-        donor: creator.publicKey,
-        campaign: campaignPda,
-        vaultLamports: vaultLamportsPda,
-        systemProgram: SystemProgram.programId,
-      })
-      .rpc();
+    await setBeneficiaryWhitelist(true);
+    try {
+      await expectError(
+        finalizeToBackup(backup, creator.publicKey, campaignPda, vaultLamportsPda),
+        /BeneficiaryNotAllowed/i
+      );
 
-    await expectError(
-      program.methods
-        .finalize()
+      await program.methods
+        .setBeneficiaryAllowed(backup.publicKey, true)
         .accounts({
-          beneficiary: unauthorized.publicKey,
-          authority: creator.publicKey,
-          campaign: campaignPda,
-          vaultLamports: vaultLamportsPda,
+          admin: provider.wallet.publicKey,
+          protocolConfig,
+          beneficiaryRegistry: findBeneficiaryRegistryPda(backup.publicKey),
           systemProgram: SystemProgram.programId,
         })
-        .signers([unauthorized])
-        .rpc(),
-      /Unauthorized|has one|ConstraintHasOne/i
-    );
+        .rpc();
+      await finalizeToBackup(backup, creator.publicKey, campaignPda, vaultLamportsPda);
+    } finally {
+      await setBeneficiaryWhitelist(false);
+    }
+
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.isTrue(campaign.isFinalized);
+    assert.equal(campaign.finalFunds.toString(), "400000");
   });
 
-  it("double finalize is rejected", async () => {
+  it("claim_vested needs a whitelisted member while the whitelist is enforced", async () => {
     const beneficiary = Keypair.generate();
-    await airdrop(beneficiary.publicKey);
-
-    const targetLamports = Math.floor(0.05 * LAMPORTS_PER_SOL);
-    const depositLamports = Math.floor(0.01 * LAMPORTS_PER_SOL);
+    const donor = Keypair.generate();
+    const member = Keypair.generate();
+    for (const kp of [beneficiary, donor, member]) await airdrop(kp.publicKey);
+    const protocolConfig = await ensureProtocolConfig();
 
     const { campaignPda, vaultLamportsPda } = await initCampaign({
       creator,
       beneficiary: beneficiary.publicKey,
-      targetLamports,
+      targetLamports: 1_000_000,
+      config: teamConfig([{ member: member.publicKey, shareBps: 10_000 }]),
     });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 1_000_000);
+    await finalizeTeamVested(beneficiary, campaignPda, vaultLamportsPda, 0, 1);
+    const teamVestingPda = findTeamVestingPda(campaignPda);
+    const vesting = await program.account.teamVesting.fetch(teamVestingPda);
+    await waitForUnixTime(vesting.start.toNumber() + 1);
 
-    await program.methods
-      .deposit(new anchor.BN(depositLamports))
-      .accounts({
-        donor: creator.publicKey,
-        campaign: campaignPda,
-        vaultLamports: vaultLamportsPda,
-        systemProgram: SystemProgram.programId,
-      })
-      .rpc();
-
-    await program.methods
-      .finalize()
-      .accounts({
-        beneficiary: beneficiary.publicKey,
-        authority: creator.publicKey,
-        campaign: campaignPda,
-        vaultLamports: vaultLamportsPda,
-        systemProgram: SystemProgram.programId,
-      })
-      .signers([beneficiary])
-      .rpc();
+    await setBeneficiaryWhitelist(true);
+    try {
+      await expectError(claimVested(member, teamVestingPda), /BeneficiaryNotAllowed/i);
 
-    await expectError(
-      program.methods
-        .finalize()
+      await program.methods
+        .setBeneficiaryAllowed(member.publicKey, true)
         .accounts({
-          beneficiary: beneficiary.publicKey,
-          authority: creator.publicKey,
-          campaign: campaignPda,
-          vaultLamports: vaultLamportsPda,
+          admin: provider.wallet.publicKey,
+          protocolConfig,
+          beneficiaryRegistry: findBeneficiaryRegistryPda(member.publicKey),
           systemProgram: SystemProgram.programId,
         })
-        .signers([beneficiary])
-        .rpc(),
-      /CampaignFinalized|Constraint.*finalized|AccountNotInitialized|AccountNotFound|closed/i
+        .rpc();
+      await claimVested(member, teamVestingPda);
+    } finally {
+      await setBeneficiaryWhitelist(false);
+    }
+
+    const stream = await program.account.vestingStream.fetch(
+      findVestingStreamPda(teamVestingPda, member.publicKey)
     );
+    assert.equal(stream.claimed.toString(), "1000000");
   });
 
-  it("deposit of zero lamports is rejected", async () => {
+  it("overflow shares are weighed by the donors' own amounts, not by matched funds", async () => {
     const beneficiary = Keypair.generate();
-    await airdrop(beneficiary.publicKey);
-
-    const targetLamports = Math.floor(0.05 * LAMPORTS_PER_SOL);
+    const a = Keypair.generate();
+    const b = Keypair.generate();
+    await airdrop(a.publicKey);
+    await airdrop(b.publicKey);
     const { campaignPda, vaultLamportsPda } = await initCampaign({
       creator,
       beneficiary: beneficiary.publicKey,
-      targetLamports,
+      targetLamports: 1_000_000,
+      config: {
+        matchTiers: [
+          { amount: new anchor.BN(200_000), rateBps: 10_000 },
+          { amount: new anchor.BN(0), rateBps: 0 },
+          { amount: new anchor.BN(0), rateBps: 0 },
+        ],
+      },
     });
+    await fundMatch(creator, campaignPda, vaultLamportsPda, 200_000);
+    // a's deposit takes the whole match pool, b's is unmatched
+    await depositFrom(a, campaignPda, vaultLamportsPda, 200_000);
+    await depositFrom(b, campaignPda, vaultLamportsPda, 400_000);
+    let campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), "800000");
+    assert.equal(campaign.contributed.toString(), "600000");
 
-    await expectError(
-      program.methods
-        .deposit(new anchor.BN(0))
-        .accounts({
-        donor: creator.publicKey,
-        campaign: campaignPda,
-        vaultLamports: vaultLamportsPda,
-        systemProgram: SystemProgram.programId,
-      })
-      .rpc(),
-      /InvalidAmount/i
-    );
+    await updateTarget(campaignPda, 500_000);
+    const balance = (key: PublicKey) => provider.connection.getBalance(key);
+    const aBefore = await balance(a.publicKey);
+    const bBefore = await balance(b.publicKey);
+    await distributeOverflow(campaignPda, beneficiary.publicKey, vaultLamportsPda, [
+      a.publicKey,
+      b.publicKey,
+    ]);
+
+    // 300_000 of overflow split 1:2 over the 600_000 the donors put in; the
+    // last donor gets no more than their own share
+    assert.equal((await balance(a.publicKey)) - aBefore, 100_000);
+    assert.equal((await balance(b.publicKey)) - bBefore, 200_000);
+    campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), "500000");
+    assert.equal(campaign.contributed.toString(), "300000");
   });
 
-  it("deposit after finalize is rejected", async () => {
+  it("assert_invariants accepts funds above a lowered target until the overflow is paid out", async () => {
     const beneficiary = Keypair.generate();
-    await airdrop(beneficiary.publicKey);
+    const a = Keypair.generate();
+    const b = Keypair.generate();
+    await airdrop(a.publicKey);
+    await airdrop(b.publicKey);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+    });
+    await depositFrom(a, campaignPda, vaultLamportsPda, 600_000);
+    await depositFrom(b, campaignPda, vaultLamportsPda, 400_000);
 
-    const targetLamports = Math.floor(0.05 * LAMPORTS_PER_SOL);
-    const depositLamports = Math.floor(0.01 * LAMPORTS_PER_SOL);
+    const assertInvariants = () =>
+      program.methods
+        .assertInvariants()
+        .accounts({ campaign: campaignPda, vaultLamports: vaultLamportsPda })
+        .rpc();
 
-    const { campaignPda, vaultLamportsPda } = await initCampaign({
+    await updateTarget(campaignPda, 500_000);
+    let campaign = await program.account.campaign.fetch(campaignPda);
+    assert.isTrue(campaign.overflowPending);
+    await assertInvariants();
+
+    await distributeOverflow(campaignPda, beneficiary.publicKey, vaultLamportsPda, [a.publicKey]);
+    campaign = await program.account.campaign.fetch(campaignPda);
+    assert.isFalse(campaign.overflowPending);
+    assert.isTrue(campaign.overflowInProgress);
+    await assertInvariants();
+
+    await distributeOverflow(campaignPda, beneficiary.publicKey, vaultLamportsPda, [b.publicKey]);
+    campaign = await program.account.campaign.fetch(campaignPda);
+    assert.isFalse(campaign.overflowInProgress);
+    assert.equal(campaign.funds.toString(), "500000");
+    await assertInvariants();
+  });
+
+  it("import_state keeps the destination's own creator bond", async () => {
+    const beneficiary = Keypair.generate();
+    const source = await initCampaign({
       creator,
       beneficiary: beneficiary.publicKey,
-      targetLamports,
+      targetLamports: 1_000_000,
     });
+    const blob: Buffer = await program.methods
+      .exportState()
+      .accounts({ campaign: source.campaignPda })
+      .view();
 
-    await program.methods
-      .deposit(new anchor.BN(depositLamports))
-      .accounts({
-        donor: creator.publicKey,
-        campaign: campaignPda,
-        vaultLamports: vaultLamportsPda,
-        systemProgram: SystemProgram.programId,
-      })
-      .rpc();
+    const dest = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 5_000_000,
+      config: { bondAmount: new anchor.BN(1_000_000) },
+    });
+    await importState(dest.campaignPda, dest.vaultLamportsPda, blob, beneficiary.publicKey);
+
+    const imported = await program.account.campaign.fetch(dest.campaignPda);
+    assert.equal(imported.target.toString(), "1000000");
+    assert.equal(imported.bondAmount.toString(), "1000000");
+    assert.equal(imported.bondState, 1);
+    const rent = await provider.connection.getMinimumBalanceForRentExemption(0);
+    assert.equal(
+      await provider.connection.getBalance(findCreatorBondPda(dest.campaignPda)),
+      1_000_000 + rent
+    );
+  });
 
+  it("deposit_delegated holds above-threshold deposits to the confirmation step", async () => {
+    const donor = Keypair.generate();
+    const relayer = Keypair.generate();
+    await airdrop(donor.publicKey);
+    await airdrop(relayer.publicKey);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+      config: { largeDepositThreshold: new anchor.BN(500_000) },
+    });
+    const authorizationPda = findDepositAuthorizationPda(campaignPda, donor.publicKey);
     await program.methods
-      .finalize()
+      .authorizeDeposit(new anchor.BN(600_000), relayer.publicKey)
       .accounts({
-        beneficiary: beneficiary.publicKey,
-        authority: creator.publicKey,
+        donor: donor.publicKey,
         campaign: campaignPda,
-        vaultLamports: vaultLamportsPda,
+        authorization: authorizationPda,
         systemProgram: SystemProgram.programId,
       })
-      .signers([beneficiary])
+      .signers([donor])
       .rpc();
 
     await expectError(
       program.methods
-        .deposit(new anchor.BN(1))
+        .depositDelegated(new anchor.BN(600_000))
         .accounts({
-          donor: creator.publicKey,
+          relayer: relayer.publicKey,
+          donor: donor.publicKey,
           campaign: campaignPda,
           vaultLamports: vaultLamportsPda,
+          authorization: authorizationPda,
+          contribution: findContributionPda(campaignPda, donor.publicKey),
+          donorProfile: findDonorProfilePda(donor.publicKey),
           systemProgram: SystemProgram.programId,
         })
+        .signers([relayer])
         .rpc(),
-      /CampaignFinalized|AccountNotInitialized|AccountNotFound|closed/i
+      /DepositRequiresConfirmation/i
     );
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), "0");
   });
 
-  it("mismatched vault lamports seed is rejected", async () => {
-    const beneficiaryA = Keypair.generate();
-    const beneficiaryB = Keypair.generate();
-    await airdrop(beneficiaryA.publicKey);
-    await airdrop(beneficiaryB.publicKey);
+  it("deposit_voucher holds above-threshold deposits to the confirmation step", async () => {
+    const donor = Keypair.generate();
+    await airdrop(donor.publicKey);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+      config: { largeDepositThreshold: new anchor.BN(500_000) },
+    });
+    await fundVoucherEscrow(donor, 700_000);
 
-    const targetLamports = Math.floor(0.05 * LAMPORTS_PER_SOL);
+    await expectError(
+      depositVoucher(donor, donor.publicKey, campaignPda, vaultLamportsPda, 600_000, 1),
+      /DepositRequiresConfirmation/i
+    );
+    await depositVoucher(donor, donor.publicKey, campaignPda, vaultLamportsPda, 500_000, 2);
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.equal(campaign.funds.toString(), "500000");
+  });
 
-    const {
-      campaignPda: campaignA,
-      vaultLamportsPda: vaultLamportsA,
-    } = await initCampaign({
+  it("each donor raises at most one dispute per campaign", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    const other = Keypair.generate();
+    const arbiter = Keypair.generate();
+    await airdrop(donor.publicKey);
+    await airdrop(other.publicKey);
+    await airdrop(arbiter.publicKey);
+
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
       creator,
-      beneficiary: beneficiaryA.publicKey,
-      targetLamports,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
+      config: { arbiter: arbiter.publicKey },
     });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 300_000);
+    await depositFrom(other, campaignPda, vaultLamportsPda, 300_000);
 
-    const {
-      campaignPda: campaignB,
-      vaultLamportsPda: vaultLamportsB,
-    } = await initCampaign({
+    await raiseDispute(donor, campaignPda);
+    await resolveDispute(arbiter, campaignPda, false);
+    await expectError(raiseDispute(donor, campaignPda), /DisputeAlreadyRaised/i);
+    let campaign = await program.account.campaign.fetch(campaignPda);
+    assert.isFalse(campaign.disputeActive);
+
+    await raiseDispute(other, campaignPda);
+    campaign = await program.account.campaign.fetch(campaignPda);
+    assert.isTrue(campaign.disputeActive);
+    assert.equal(campaign.disputeCount, 2);
+  });
+
+  it("unlock_content with a zero threshold still needs a contribution", async () => {
+    const donor = Keypair.generate();
+    const stranger = Keypair.generate();
+    await airdrop(donor.publicKey);
+    await airdrop(stranger.publicKey);
+
+    const contentHash = [...createHash("sha256").update("any-backer").digest()];
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
       creator,
-      beneficiary: beneficiaryB.publicKey,
-      targetLamports,
+      beneficiary: Keypair.generate().publicKey,
+      targetLamports: 1_000_000,
+      config: { gatedContentHash: contentHash, unlockThreshold: new anchor.BN(0) },
+    });
+
+    await expectError(unlockContent(stranger, campaignPda), /ContentLocked/i);
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 1);
+    assert.deepEqual([...(await unlockContent(donor, campaignPda))], contentHash);
+  });
+
+  it("reveal_contribution is refused once the campaign is finalized and cleaned up", async () => {
+    const beneficiary = Keypair.generate();
+    const donor = Keypair.generate();
+    const hidden = Keypair.generate();
+    await airdrop(beneficiary.publicKey);
+    await airdrop(donor.publicKey);
+    await airdrop(hidden.publicKey);
+    const { campaignPda, vaultLamportsPda } = await initCampaign({
+      creator,
+      beneficiary: beneficiary.publicKey,
+      targetLamports: 1_000_000,
     });
+    await depositFrom(donor, campaignPda, vaultLamportsPda, 600_000);
+    const salt = randomBytes(32);
+    const commitment = donorCommitment(hidden.publicKey, salt);
+    await depositAnonymous(hidden, campaignPda, vaultLamportsPda, 400_000, commitment);
+
+    await finalizeAndCleanup(beneficiary, campaignPda, vaultLamportsPda, [
+      findContributionPda(campaignPda, donor.publicKey),
+    ]);
+    const campaign = await program.account.campaign.fetch(campaignPda);
+    assert.isTrue(campaign.fullyCleaned);
 
     await expectError(
-      program.methods
-        .deposit(new anchor.BN(1_000))
-        .accounts({
-          donor: creator.publicKey,
-          campaign: campaignA,
-          vaultLamports: vaultLamportsB,
-          systemProgram: SystemProgram.programId,
-        })
-        .rpc(),
-      /ConstraintSeeds|seeds constraint/i
+      revealContribution(hidden, campaignPda, commitment, salt),
+      /CampaignFinalized/i
+    );
+    assert.isNull(
+      await provider.connection.getAccountInfo(findContributionPda(campaignPda, hidden.publicKey))
     );
   });
 });